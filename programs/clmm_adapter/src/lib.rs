@@ -0,0 +1,513 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, set_return_data};
+use anchor_lang::solana_program::pubkey;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("BZ1MPcHtnz9zjLdU3HpENuJ1wMUPRWJA621eQh4y6dLL");
+
+pub const WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+// Anchor discriminators of the Whirlpool and Position accounts
+const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+
+// Byte offsets into a Whirlpool account
+const WHIRLPOOL_SQRT_PRICE: usize = 65;
+const WHIRLPOOL_TICK_CURRENT: usize = 81;
+const WHIRLPOOL_MINT_A: usize = 101;
+const WHIRLPOOL_VAULT_A: usize = 133;
+const WHIRLPOOL_MINT_B: usize = 181;
+const WHIRLPOOL_VAULT_B: usize = 213;
+const WHIRLPOOL_MIN_LEN: usize = 245;
+
+// Byte offsets into a Position account
+const POSITION_WHIRLPOOL: usize = 8;
+const POSITION_MINT: usize = 40;
+const POSITION_LIQUIDITY: usize = 72;
+const POSITION_TICK_LOWER: usize = 88;
+const POSITION_TICK_UPPER: usize = 92;
+const POSITION_FEE_OWED_A: usize = 112;
+const POSITION_FEE_OWED_B: usize = 136;
+const POSITION_MIN_LEN: usize = 144;
+
+// sqrt(1.0001): each tick moves the pool's sqrt price by this factor
+const SQRT_TICK_BASE: f64 = 1.000_049_998_750_062_4;
+const Q64: f64 = 18_446_744_073_709_551_616.0;
+
+// Liquidity is sized slightly under the deposit so rounding never asks the
+// pool for more USDC than the vault offered
+const LIQUIDITY_HAIRCUT: f64 = 1e-9;
+
+// Price tolerance on partial withdrawals
+const MAX_SLIPPAGE_BPS: u64 = 50;
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[program]
+pub mod clmm_adapter {
+    use super::*;
+
+    pub fn whitelist_pool(ctx: Context<WhitelistPool>) -> Result<()> {
+        require!(
+            ctx.accounts.program.programdata_address()? == Some(ctx.accounts.program_data.key())
+                && ctx.accounts.program_data.upgrade_authority_address
+                    == Some(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.pool_entry.whirlpool = ctx.accounts.whirlpool.key();
+        Ok(())
+    }
+
+    pub fn remove_pool(ctx: Context<RemovePool>) -> Result<()> {
+        require!(
+            ctx.accounts.program.programdata_address()? == Some(ctx.accounts.program_data.key())
+                && ctx.accounts.program_data.upgrade_authority_address
+                    == Some(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    // Add `amount` of vault USDC as liquidity to the vault's position. Deposits
+    // are single-sided, so the range must sit entirely on the USDC side of the
+    // current price
+    pub fn deposit(ctx: Context<Supply>, amount: u64) -> Result<()> {
+        // Removed pools can still be withdrawn from, but not deposited to
+        require!(
+            ctx.accounts.pool_entry.is_some(),
+            ErrorCode::PoolNotWhitelisted
+        );
+
+        let state = ctx.accounts.load()?;
+        let width = state.sqrt_upper - state.sqrt_lower;
+        let liquidity = if state.usdc_is_a {
+            require!(
+                state.tick_current < state.tick_lower,
+                ErrorCode::PositionInRange
+            );
+            amount as f64 * state.sqrt_lower * state.sqrt_upper / width
+        } else {
+            require!(
+                state.tick_current >= state.tick_upper,
+                ErrorCode::PositionInRange
+            );
+            amount as f64 / width
+        };
+        let liquidity = (liquidity * (1.0 - LIQUIDITY_HAIRCUT)) as u128;
+        require!(liquidity > 0, ErrorCode::AmountTooSmall);
+
+        let (max_a, max_b) = if state.usdc_is_a { (amount, 0) } else { (0, amount) };
+        ctx.accounts
+            .modify_liquidity("increase_liquidity", state.usdc_is_a, liquidity, max_a, max_b)
+    }
+
+    // Remove liquidity worth `amount` of USDC and collect fees. `u64::MAX` is a
+    // full exit that pulls all liquidity without price limits, so an emergency
+    // withdrawal can't be blocked by a moving pool. Any non-USDC proceeds stay
+    // in the vault signer's other token account and keep counting in
+    // `total_value`
+    pub fn withdraw(ctx: Context<Supply>, amount: u64) -> Result<()> {
+        let state = ctx.accounts.load()?;
+
+        let (liquidity, min_usdc, min_other) = if amount == u64::MAX {
+            (state.liquidity, 0, 0)
+        } else {
+            let value = state.value(state.liquidity);
+            let liquidity = if value <= amount as f64 {
+                state.liquidity
+            } else {
+                (state.liquidity as f64 * amount as f64 / value) as u128
+            };
+            let (usdc, other) = state.amounts(liquidity);
+            (liquidity, with_slippage(usdc), with_slippage(other))
+        };
+
+        if liquidity > 0 {
+            let (min_a, min_b) = if state.usdc_is_a {
+                (min_usdc, min_other)
+            } else {
+                (min_other, min_usdc)
+            };
+            ctx.accounts
+                .modify_liquidity("decrease_liquidity", state.usdc_is_a, liquidity, min_a, min_b)?;
+        }
+        ctx.accounts.collect_fees(state.usdc_is_a)
+    }
+
+    // USDC value of the position, its owed fees and any non-USDC balance at
+    // the pool price, handed back to the vault program as return data. The
+    // spot price can be moved within a transaction, so the vault only asks
+    // for it from keeper-gated instructions
+    pub fn total_value(ctx: Context<Supply>, _amount: u64) -> Result<()> {
+        let state = ctx.accounts.load()?;
+
+        let (usdc, other) = state.amounts(state.liquidity);
+        let other = other
+            + state.fee_owed_other as f64
+            + ctx.accounts.other_token_account.amount as f64;
+        let value = usdc + state.fee_owed_usdc as f64 + state.other_in_usdc(other);
+
+        set_return_data(&(value as u64).to_le_bytes());
+        Ok(())
+    }
+}
+
+fn sighash(name: &str) -> Vec<u8> {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec()
+}
+
+fn with_slippage(amount: f64) -> u64 {
+    (amount as u64)
+        .checked_mul(BPS_DENOMINATOR - MAX_SLIPPAGE_BPS)
+        .unwrap()
+        / BPS_DENOMINATOR
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// Pool and position state read from the Whirlpool accounts, with sqrt prices
+// as plain ratios rather than Q64.64
+struct PositionState {
+    usdc_is_a: bool,
+    sqrt_price: f64,
+    sqrt_lower: f64,
+    sqrt_upper: f64,
+    tick_current: i32,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    fee_owed_usdc: u64,
+    fee_owed_other: u64,
+}
+
+impl PositionState {
+    // (USDC, other token) amounts backing `liquidity` at the current price
+    fn amounts(&self, liquidity: u128) -> (f64, f64) {
+        let liquidity = liquidity as f64;
+        let (price, lower, upper) = (self.sqrt_price, self.sqrt_lower, self.sqrt_upper);
+        let (a, b) = if price <= lower {
+            (liquidity * (upper - lower) / (lower * upper), 0.0)
+        } else if price >= upper {
+            (0.0, liquidity * (upper - lower))
+        } else {
+            (
+                liquidity * (upper - price) / (price * upper),
+                liquidity * (price - lower),
+            )
+        };
+        if self.usdc_is_a {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    // Pool price is token B per token A
+    fn other_in_usdc(&self, other: f64) -> f64 {
+        let price = self.sqrt_price * self.sqrt_price;
+        if self.usdc_is_a {
+            other / price
+        } else {
+            other * price
+        }
+    }
+
+    fn value(&self, liquidity: u128) -> f64 {
+        let (usdc, other) = self.amounts(liquidity);
+        usdc + self.other_in_usdc(other)
+    }
+}
+
+#[derive(Accounts)]
+pub struct WhitelistPool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolEntry::LEN,
+        seeds = [b"pool", whirlpool.key().as_ref()],
+        bump,
+    )]
+    pub pool_entry: Account<'info, PoolEntry>,
+
+    /// CHECK: any pool owned by the Whirlpool program
+    #[account(owner = WHIRLPOOL_PROGRAM_ID)]
+    pub whirlpool: UncheckedAccount<'info>,
+
+    pub program: Program<'info, crate::program::ClmmAdapter>,
+
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePool<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"pool", pool_entry.whirlpool.as_ref()],
+        bump,
+    )]
+    pub pool_entry: Account<'info, PoolEntry>,
+
+    pub program: Program<'info, crate::program::ClmmAdapter>,
+
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Account order is fixed by the vault program: vault signer and vault USDC
+// account first, then the adapter's own accounts
+#[derive(Accounts)]
+pub struct Supply<'info> {
+    pub vault_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"pool", whirlpool.key().as_ref()],
+        bump,
+    )]
+    pub pool_entry: Option<Account<'info, PoolEntry>>,
+
+    /// CHECK: layout, mints and vaults are verified on load
+    #[account(
+        mut,
+        owner = WHIRLPOOL_PROGRAM_ID,
+    )]
+    pub whirlpool: UncheckedAccount<'info>,
+
+    /// CHECK: layout and pool are verified on load
+    #[account(
+        mut,
+        owner = WHIRLPOOL_PROGRAM_ID,
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    #[account(
+        token::authority = vault_signer,
+    )]
+    pub position_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = vault_signer,
+    )]
+    pub other_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: must be the pool's token A vault, verified on load
+    #[account(mut)]
+    pub token_vault_a: UncheckedAccount<'info>,
+
+    /// CHECK: must be the pool's token B vault, verified on load
+    #[account(mut)]
+    pub token_vault_b: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the Whirlpool program
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the Whirlpool program
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    /// CHECK: must be the Whirlpool program
+    #[account(address = WHIRLPOOL_PROGRAM_ID)]
+    pub whirlpool_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Supply<'info> {
+    fn load(&self) -> Result<PositionState> {
+        let pool = self.whirlpool.try_borrow_data()?;
+        let position = self.position.try_borrow_data()?;
+        require!(
+            pool.len() >= WHIRLPOOL_MIN_LEN && pool[..8] == WHIRLPOOL_DISCRIMINATOR,
+            ErrorCode::InvalidPool
+        );
+        require!(
+            position.len() >= POSITION_MIN_LEN && position[..8] == POSITION_DISCRIMINATOR,
+            ErrorCode::InvalidPosition
+        );
+
+        // Pool must pair the vault's USDC with the other token account's mint
+        let usdc = self.vault_usdc_account.mint;
+        let other = self.other_token_account.mint;
+        let mint_a = read_pubkey(&pool, WHIRLPOOL_MINT_A);
+        let mint_b = read_pubkey(&pool, WHIRLPOOL_MINT_B);
+        let usdc_is_a = mint_a == usdc && mint_b == other;
+        require!(
+            usdc_is_a || (mint_a == other && mint_b == usdc),
+            ErrorCode::InvalidPool
+        );
+        require!(
+            read_pubkey(&pool, WHIRLPOOL_VAULT_A) == self.token_vault_a.key()
+                && read_pubkey(&pool, WHIRLPOOL_VAULT_B) == self.token_vault_b.key(),
+            ErrorCode::InvalidPool
+        );
+
+        // Position must belong to this pool and be held by the vault signer
+        require!(
+            read_pubkey(&position, POSITION_WHIRLPOOL) == self.whirlpool.key()
+                && read_pubkey(&position, POSITION_MINT) == self.position_token_account.mint
+                && self.position_token_account.amount == 1,
+            ErrorCode::InvalidPosition
+        );
+
+        let tick_lower = read_i32(&position, POSITION_TICK_LOWER);
+        let tick_upper = read_i32(&position, POSITION_TICK_UPPER);
+        let fee_owed_a = read_u64(&position, POSITION_FEE_OWED_A);
+        let fee_owed_b = read_u64(&position, POSITION_FEE_OWED_B);
+        let (fee_owed_usdc, fee_owed_other) = if usdc_is_a {
+            (fee_owed_a, fee_owed_b)
+        } else {
+            (fee_owed_b, fee_owed_a)
+        };
+
+        Ok(PositionState {
+            usdc_is_a,
+            sqrt_price: read_u128(&pool, WHIRLPOOL_SQRT_PRICE) as f64 / Q64,
+            sqrt_lower: SQRT_TICK_BASE.powi(tick_lower),
+            sqrt_upper: SQRT_TICK_BASE.powi(tick_upper),
+            tick_current: read_i32(&pool, WHIRLPOOL_TICK_CURRENT),
+            tick_lower,
+            tick_upper,
+            liquidity: read_u128(&position, POSITION_LIQUIDITY),
+            fee_owed_usdc,
+            fee_owed_other,
+        })
+    }
+
+    // Vault token accounts in the pool's (A, B) order
+    fn owner_accounts(&self, usdc_is_a: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        let usdc = self.vault_usdc_account.to_account_info();
+        let other = self.other_token_account.to_account_info();
+        if usdc_is_a {
+            (usdc, other)
+        } else {
+            (other, usdc)
+        }
+    }
+
+    // Shared account layout of increase_liquidity and decrease_liquidity
+    fn modify_liquidity(
+        &self,
+        name: &str,
+        usdc_is_a: bool,
+        liquidity: u128,
+        token_a: u64,
+        token_b: u64,
+    ) -> Result<()> {
+        let mut data = sighash(name);
+        data.extend_from_slice(&liquidity.to_le_bytes());
+        data.extend_from_slice(&token_a.to_le_bytes());
+        data.extend_from_slice(&token_b.to_le_bytes());
+
+        let (owner_a, owner_b) = self.owner_accounts(usdc_is_a);
+        let ix = Instruction {
+            program_id: WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.whirlpool.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+                AccountMeta::new_readonly(self.vault_signer.key(), true),
+                AccountMeta::new(self.position.key(), false),
+                AccountMeta::new_readonly(self.position_token_account.key(), false),
+                AccountMeta::new(owner_a.key(), false),
+                AccountMeta::new(owner_b.key(), false),
+                AccountMeta::new(self.token_vault_a.key(), false),
+                AccountMeta::new(self.token_vault_b.key(), false),
+                AccountMeta::new(self.tick_array_lower.key(), false),
+                AccountMeta::new(self.tick_array_upper.key(), false),
+            ],
+            data,
+        };
+        invoke(&ix, &self.whirlpool_infos())?;
+        Ok(())
+    }
+
+    fn collect_fees(&self, usdc_is_a: bool) -> Result<()> {
+        let (owner_a, owner_b) = self.owner_accounts(usdc_is_a);
+        let ix = Instruction {
+            program_id: WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.whirlpool.key(), false),
+                AccountMeta::new_readonly(self.vault_signer.key(), true),
+                AccountMeta::new(self.position.key(), false),
+                AccountMeta::new_readonly(self.position_token_account.key(), false),
+                AccountMeta::new(owner_a.key(), false),
+                AccountMeta::new(self.token_vault_a.key(), false),
+                AccountMeta::new(owner_b.key(), false),
+                AccountMeta::new(self.token_vault_b.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data: sighash("collect_fees"),
+        };
+        invoke(&ix, &self.whirlpool_infos())?;
+        Ok(())
+    }
+
+    fn whirlpool_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.whirlpool.to_account_info(),
+            self.token_program.to_account_info(),
+            self.vault_signer.to_account_info(),
+            self.position.to_account_info(),
+            self.position_token_account.to_account_info(),
+            self.vault_usdc_account.to_account_info(),
+            self.other_token_account.to_account_info(),
+            self.token_vault_a.to_account_info(),
+            self.token_vault_b.to_account_info(),
+            self.tick_array_lower.to_account_info(),
+            self.tick_array_upper.to_account_info(),
+            self.whirlpool_program.to_account_info(),
+        ]
+    }
+}
+
+#[account]
+pub struct PoolEntry {
+    pub whirlpool: Pubkey,
+}
+
+impl PoolEntry {
+    pub const LEN: usize = 32; // whirlpool (Pubkey)
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Pool is not whitelisted")]
+    PoolNotWhitelisted,
+    #[msg("Pool does not pair the vault USDC with the given token")]
+    InvalidPool,
+    #[msg("Position is not a vault position in this pool")]
+    InvalidPosition,
+    #[msg("Position range includes the current price; deposits must be single-sided")]
+    PositionInRange,
+    #[msg("Amount is too small to add liquidity")]
+    AmountTooSmall,
+}
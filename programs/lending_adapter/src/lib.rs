@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, set_return_data};
+use anchor_lang::solana_program::pubkey;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("GQPVcGMq979a96c9YuZhJ5yH4gzbcK1fSMkc6nf6UKfq");
+
+// Whitelisted token-lending program and market (Solend main pool)
+pub const LENDING_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+pub const LENDING_MARKET: Pubkey = pubkey!("4UpD2fh7xH3VP9QQaXtsS1YY3bxzWhtfpks7FatyKvdY");
+
+// Token-lending instruction tags
+const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+const REDEEM_RESERVE_COLLATERAL: u8 = 5;
+
+// Byte offsets into a token-lending Reserve account
+const RESERVE_LENDING_MARKET: usize = 10;
+const RESERVE_LIQUIDITY_MINT: usize = 42;
+const RESERVE_AVAILABLE_AMOUNT: usize = 171;
+const RESERVE_BORROWED_AMOUNT_WADS: usize = 179;
+const RESERVE_COLLATERAL_MINT: usize = 227;
+const RESERVE_COLLATERAL_SUPPLY: usize = 259;
+const RESERVE_MIN_LEN: usize = 267;
+
+const WAD: u128 = 1_000_000_000_000_000_000;
+
+#[program]
+pub mod lending_adapter {
+    use super::*;
+
+    // Supply `amount` of vault USDC to the reserve; the cTokens are minted to
+    // a collateral account owned by the vault signer
+    pub fn deposit(ctx: Context<Supply>, amount: u64) -> Result<()> {
+        ctx.accounts.check_reserve()?;
+
+        let ix = Instruction {
+            program_id: LENDING_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.vault_usdc_account.key(), false),
+                AccountMeta::new(ctx.accounts.collateral_account.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_signer.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: lending_data(DEPOSIT_RESERVE_LIQUIDITY, amount),
+        };
+        invoke(&ix, &ctx.accounts.lending_infos())?;
+
+        Ok(())
+    }
+
+    // Redeem the cTokens worth `amount` of USDC, rounded down, back into the
+    // vault USDC account; `u64::MAX` redeems the whole collateral balance
+    pub fn withdraw(ctx: Context<Supply>, amount: u64) -> Result<()> {
+        ctx.accounts.check_reserve()?;
+
+        let collateral = if amount == u64::MAX {
+            ctx.accounts.collateral_account.amount
+        } else {
+            let (liquidity_wads, collateral_supply) = ctx.accounts.exchange_rate()?;
+            (amount as u128)
+                .checked_mul(collateral_supply)
+                .unwrap()
+                .checked_mul(WAD)
+                .unwrap()
+                .checked_div(liquidity_wads)
+                .unwrap()
+                .min(ctx.accounts.collateral_account.amount as u128) as u64
+        };
+
+        let ix = Instruction {
+            program_id: LENDING_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.collateral_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_usdc_account.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_signer.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: lending_data(REDEEM_RESERVE_COLLATERAL, collateral),
+        };
+        invoke(&ix, &ctx.accounts.lending_infos())?;
+
+        Ok(())
+    }
+
+    // USDC value of the vault's cTokens at the reserve exchange rate, handed
+    // back to the vault program as return data
+    pub fn total_value(ctx: Context<Supply>, _amount: u64) -> Result<()> {
+        ctx.accounts.check_reserve()?;
+
+        let (liquidity_wads, collateral_supply) = ctx.accounts.exchange_rate()?;
+        let value = if collateral_supply == 0 {
+            0
+        } else {
+            (ctx.accounts.collateral_account.amount as u128)
+                .checked_mul(liquidity_wads)
+                .unwrap()
+                .checked_div(collateral_supply)
+                .unwrap()
+                .checked_div(WAD)
+                .unwrap() as u64
+        };
+
+        set_return_data(&value.to_le_bytes());
+        Ok(())
+    }
+}
+
+fn lending_data(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// Account order is fixed by the vault program: vault signer and vault USDC
+// account first, then the adapter's own accounts
+#[derive(Accounts)]
+pub struct Supply<'info> {
+    pub vault_signer: Signer<'info>,
+
+    #[account(mut)]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = vault_signer,
+    )]
+    pub collateral_account: Account<'info, TokenAccount>,
+
+    /// CHECK: owner and market are verified against the whitelist
+    #[account(
+        mut,
+        owner = LENDING_PROGRAM_ID,
+    )]
+    pub reserve: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the lending program
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the lending program
+    #[account(mut)]
+    pub reserve_collateral_mint: UncheckedAccount<'info>,
+
+    /// CHECK: must be the whitelisted lending market
+    #[account(address = LENDING_MARKET)]
+    pub lending_market: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the lending program
+    pub lending_market_authority: UncheckedAccount<'info>,
+
+    /// CHECK: must be the whitelisted lending program
+    #[account(address = LENDING_PROGRAM_ID)]
+    pub lending_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Supply<'info> {
+    // Reserve must belong to the whitelisted market and match the vault's
+    // USDC mint and collateral account
+    fn check_reserve(&self) -> Result<()> {
+        let data = self.reserve.try_borrow_data()?;
+        require!(data.len() >= RESERVE_MIN_LEN, ErrorCode::InvalidReserve);
+        require!(
+            read_pubkey(&data, RESERVE_LENDING_MARKET) == LENDING_MARKET
+                && read_pubkey(&data, RESERVE_LIQUIDITY_MINT) == self.vault_usdc_account.mint
+                && read_pubkey(&data, RESERVE_COLLATERAL_MINT) == self.collateral_account.mint
+                && self.reserve_collateral_mint.key() == self.collateral_account.mint,
+            ErrorCode::InvalidReserve
+        );
+        Ok(())
+    }
+
+    // Total reserve liquidity (in WADs) and cToken supply
+    fn exchange_rate(&self) -> Result<(u128, u128)> {
+        let data = self.reserve.try_borrow_data()?;
+        let available = read_u64(&data, RESERVE_AVAILABLE_AMOUNT) as u128;
+        let borrowed_wads = read_u128(&data, RESERVE_BORROWED_AMOUNT_WADS);
+        let collateral_supply = read_u64(&data, RESERVE_COLLATERAL_SUPPLY) as u128;
+        let liquidity_wads = available.checked_mul(WAD).unwrap().checked_add(borrowed_wads).unwrap();
+        Ok((liquidity_wads, collateral_supply))
+    }
+
+    fn lending_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.vault_usdc_account.to_account_info(),
+            self.collateral_account.to_account_info(),
+            self.reserve.to_account_info(),
+            self.reserve_liquidity_supply.to_account_info(),
+            self.reserve_collateral_mint.to_account_info(),
+            self.lending_market.to_account_info(),
+            self.lending_market_authority.to_account_info(),
+            self.vault_signer.to_account_info(),
+            self.token_program.to_account_info(),
+            self.lending_program.to_account_info(),
+        ]
+    }
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Reserve is not part of the whitelisted lending market")]
+    InvalidReserve,
+}
@@ -1,12 +1,131 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::program::{get_return_data, invoke, invoke_signed};
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 use anchor_spl::{
-    token::{self, Mint, Token, TokenAccount, Transfer, MintTo, Burn},
-    associated_token::AssociatedToken,
+    token::{self, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_2022::{
+        spl_token_2022::{
+            extension::{
+                confidential_transfer, transfer_fee::TransferFeeConfig,
+                transfer_hook::TransferHookAccount, BaseStateWithExtensions, ExtensionType,
+                StateWithExtensions,
+            },
+            onchain,
+            solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey,
+            state::{Account as Token2022Account, Mint as Token2022Mint},
+        },
+        Token2022,
+    },
+    token_interface::{
+        self,
+        spl_token_metadata_interface::state::{Field, TokenMetadata},
+        spl_token_2022::instruction::AuthorityType,
+        TokenInterface, TokenMetadataInitialize, TransferChecked,
+    },
 };
 use pyth_sdk_solana::load_price_feed_from_account_info;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+// Delay between scheduling and executing a vault signer rotation
+pub const SIGNER_ROTATION_DELAY: i64 = 2 * 24 * 60 * 60;
+
+// Vault pause flags
+pub const PAUSE_DEPOSITS: u8 = 1 << 0;
+pub const PAUSE_REDEMPTIONS: u8 = 1 << 1;
+pub const PAUSE_ALL: u8 = PAUSE_DEPOSITS | PAUSE_REDEMPTIONS;
+
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+// Length of the rolling window for the admin withdrawal cap
+pub const WITHDRAW_EPOCH_DURATION: i64 = 24 * 60 * 60;
+
+// Number of co-admin slots on the Admin account
+pub const MAX_AUTHORITIES: usize = 5;
+
+pub const MAX_REASON_LEN: usize = 64;
+pub const MAX_URI_LEN: usize = 200;
+
+// Supported share mint precisions
+pub const SHARE_DECIMALS: [u8; 3] = [6, 8, 9];
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_FEE_BPS: u16 = 1_000; // 10%
+pub const MAX_PERFORMANCE_FEE_BPS: u16 = 5_000; // 50%
+pub const MAX_HAIRCUT_BPS: u16 = 2_000; // 20%
+
+// Upper bound on the deposit-to-redeem holding period (~1 day of slots)
+pub const MAX_REDEEM_COOLDOWN_SLOTS: u64 = 216_000;
+// Upper bound on the length of a queued redemption epoch
+pub const MAX_REDEEM_EPOCH_DURATION: i64 = 7 * 24 * 60 * 60;
+
+// Fixed-point scale of the reward pool's cumulative reward-per-share index
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+// Bounds on a vote-escrow lock; voting power is full at the maximum duration
+pub const MIN_VE_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;
+pub const MAX_VE_LOCK_DURATION: i64 = 4 * SECONDS_PER_YEAR;
+
+// Upper bound on how long harvested profit takes to unlock
+pub const MAX_PROFIT_UNLOCK_PERIOD: i64 = 7 * 24 * 60 * 60;
+// Time for the redemption rate limiter to refill from empty
+pub const REDEEM_RATE_WINDOW: i64 = 24 * 60 * 60;
+
+// Vault-signer token accounts a strategy's adapter may hold positions in
+pub const MAX_STRATEGY_ACCOUNTS: usize = 4;
+
+// Index vault limits: basket size, oracle staleness in seconds and the
+// widest accepted confidence interval
+pub const MAX_BASKET_COMPONENTS: usize = 8;
+pub const MAX_ORACLE_AGE: i64 = 60;
+pub const MAX_ORACLE_CONF_BPS: u64 = 200; // 2%
+
+// A batch deposit leg is seven accounts; legs per batch are capped to stay
+// within compute limits
+pub const BATCH_LEG_ACCOUNTS: usize = 7;
+pub const MAX_BATCH_LEGS: usize = 8;
+
+// Each asset in a batch creation is [asset, vault, vault_signer, mint,
+// ticker_claim, bond, stats]
+pub const BATCH_ASSET_ACCOUNTS: usize = 7;
+pub const MAX_BATCH_ASSETS: usize = 5;
+
+// Registry capacity, and the most entries list_assets returns in one call
+// to stay under the 1KB return data limit
+pub const MAX_REGISTRY_ENTRIES: usize = 1_024;
+pub const MAX_REGISTRY_PAGE: u64 = 12;
+
+// Longest memo carried on a deposit or redemption event, in bytes
+pub const MAX_MEMO_LEN: usize = 128;
+
+// Events are logged by default. With the `cpi-events` feature they are
+// written as self-CPI instruction data instead, which indexers can recover
+// even when logs are truncated; emitting instructions then take the
+// `event_authority` and `program` accounts added by `event_cpi`
+#[cfg(not(feature = "cpi-events"))]
+macro_rules! emit_event {
+    ($accounts:expr, $event:expr) => {
+        emit!($event)
+    };
+}
+
+#[cfg(feature = "cpi-events")]
+macro_rules! emit_event {
+    ($accounts:expr, $event:expr) => {
+        emit_self_cpi(&$accounts.event_authority, &$event)?
+    };
+}
+
 #[program]
 pub mod solana4626 {
     use super::*;
@@ -14,344 +133,13777 @@ pub mod solana4626 {
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let admin = &mut ctx.accounts.admin;
         admin.authority = ctx.accounts.authority.key();
+        admin.guardian = ctx.accounts.authority.key();
+        admin.signer_version = 0;
+        admin.rotation_unlock_ts = 0;
+        admin.withdraw_threshold = 1;
         Ok(())
     }
 
-    pub fn create_asset(
-        ctx: Context<CreateAsset>,
-        name: String,
-        ticker: String,
-        price: u64,
-        deposit_limit: u64,
-    ) -> Result<()> {
-        require!(name.len() <= 50, ErrorCode::NameTooLong);
-        require!(ticker.len() <= 10, ErrorCode::TickerTooLong);
-        
-        let asset = &mut ctx.accounts.asset;
-        asset.name = name;
-        asset.ticker = ticker;
-        asset.price = price;
-        asset.mint = ctx.accounts.mint.key();
-        asset.vault = ctx.accounts.vault.key();
-        asset.authority = ctx.accounts.authority.key();
+    pub fn set_guardian(ctx: Context<UpdateAdmin>, guardian: Pubkey) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
 
-        let vault = &mut ctx.accounts.vault;
-        vault.deposit_limit = deposit_limit;
+        admin.guardian = guardian;
+        Ok(())
+    }
+
+    pub fn propose_authority(ctx: Context<UpdateAdmin>, new_authority: Pubkey) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        admin.pending_authority = new_authority;
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.pending_authority != Pubkey::default()
+                && admin.pending_authority == ctx.accounts.new_authority.key(),
+            ErrorCode::NotPendingAuthority
+        );
+
+        emit_event!(ctx.accounts, AdminAuthorityTransferred {
+            old_authority: admin.authority,
+            new_authority: admin.pending_authority,
+        });
+        admin.authority = admin.pending_authority;
+        admin.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn renounce_authority(ctx: Context<UpdateAdmin>) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Role grants and co-admins lose their power along with the root
+        emit_event!(ctx.accounts, AdminAuthorityTransferred {
+            old_authority: admin.authority,
+            new_authority: Pubkey::default(),
+        });
+        admin.authority = Pubkey::default();
+        admin.pending_authority = Pubkey::default();
+        admin.authorities = [Pubkey::default(); MAX_AUTHORITIES];
+        Ok(())
+    }
+
+    pub fn add_authority(ctx: Context<UpdateAdmin>, new_authority: Pubkey) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            new_authority != Pubkey::default() && !admin.is_authority(new_authority),
+            ErrorCode::AuthorityAlreadyExists
+        );
+
+        let slot = admin
+            .authorities
+            .iter_mut()
+            .find(|key| **key == Pubkey::default())
+            .ok_or(ErrorCode::AuthorityListFull)?;
+        *slot = new_authority;
+        Ok(())
+    }
+
+    pub fn remove_authority(ctx: Context<UpdateAdmin>, authority: Pubkey) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(authority != Pubkey::default(), ErrorCode::AuthorityNotFound);
+
+        let slot = admin
+            .authorities
+            .iter_mut()
+            .find(|key| **key == authority)
+            .ok_or(ErrorCode::AuthorityNotFound)?;
+        *slot = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.authority_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let role_grant = &mut ctx.accounts.role_grant;
+        role_grant.holder = holder;
+        role_grant.role = role;
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.authority_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn schedule_signer_rotation(ctx: Context<RotateSigner>) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.guardian == ctx.accounts.guardian.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        admin.rotation_unlock_ts = now.checked_add(SIGNER_ROTATION_DELAY).unwrap();
+        Ok(())
+    }
+
+    pub fn cancel_signer_rotation(ctx: Context<UpdateAdmin>) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(admin.rotation_unlock_ts != 0, ErrorCode::RotationNotScheduled);
+
+        admin.rotation_unlock_ts = 0;
+        Ok(())
+    }
+
+    pub fn execute_signer_rotation(ctx: Context<RotateSigner>) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.guardian == ctx.accounts.guardian.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(admin.rotation_unlock_ts != 0, ErrorCode::RotationNotScheduled);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= admin.rotation_unlock_ts,
+            ErrorCode::RotationTimelockActive
+        );
 
+        // Every vault is locked out until it migrates to the new signer
+        admin.signer_version = admin.signer_version.checked_add(1).unwrap();
+        admin.rotation_unlock_ts = 0;
         Ok(())
     }
 
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    pub fn migrate_vault_signer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateVaultSigner<'info>>,
+    ) -> Result<()> {
         let asset = &ctx.accounts.asset;
         let vault = &mut ctx.accounts.vault;
-        
-        // Check if current deposit plus existing stablecoins would exceed limit
-        let new_total = vault.total_usdc.checked_add(amount).unwrap();
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        let new_version = ctx.accounts.admin.signer_version;
         require!(
-            new_total <= vault.deposit_limit,
-            ErrorCode::DepositLimitExceeded
+            vault.signer_version != new_version,
+            ErrorCode::VaultSignerCurrent
+        );
+        // Strategy positions and quote tokens can't be handed to the new
+        // signer here, so they are unwound into the vault first
+        require!(
+            vault.strategy_debt == 0 && vault.strategy_gain == 0 && vault.quote_backing == 0,
+            ErrorCode::SignerAccountsNotMigrated
         );
-        
-        // Calculate asset tokens to mint based on USDC amount and price
-        let asset_amount = amount
-            .checked_mul(1_000_000) // Convert to 6 decimals
-            .unwrap()
-            .checked_div(asset.price)
-            .unwrap();
 
-        // Transfer USDC from user to vault
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_usdc_account.to_account_info(),
-                to: ctx.accounts.vault_usdc_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+        let new_signer = ctx.accounts.new_vault_signer.key();
+
+        // Move ownership of the vault USDC account to the new signer
+        let owner_ctx = CpiContext::new_with_signer(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            token_interface::SetAuthority {
+                current_authority: ctx.accounts.old_vault_signer.to_account_info(),
+                account_or_mint: ctx.accounts.vault_usdc_account.to_account_info(),
             },
+            signer,
         );
-        token::transfer(transfer_ctx, amount)?;
+        token_interface::set_authority(owner_ctx, AuthorityType::AccountOwner, Some(new_signer))?;
 
-        // Mint asset tokens to user
-        let mint_ctx = CpiContext::new(
+        // Move the asset mint authority to the new signer
+        let mint_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.asset_mint.to_account_info(),
-                to: ctx.accounts.user_asset_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
+            token_interface::SetAuthority {
+                current_authority: ctx.accounts.old_vault_signer.to_account_info(),
+                account_or_mint: ctx.accounts.asset_mint.to_account_info(),
             },
+            signer,
         );
-        token::mint_to(mint_ctx, asset_amount)?;
+        token_interface::set_authority(mint_ctx, AuthorityType::MintTokens, Some(new_signer))?;
 
-        // Update vault state
-        vault.total_usdc = new_total;
-        vault.total_assets = vault.total_assets.checked_add(asset_amount).unwrap();
+        // Freeze authority follows the signer too when the vault holds it
+        let old_signer = ctx.accounts.old_vault_signer.key();
+        if ctx.accounts.asset_mint.freeze_authority == Some(old_signer).into() {
+            let freeze_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::SetAuthority {
+                    current_authority: ctx.accounts.old_vault_signer.to_account_info(),
+                    account_or_mint: ctx.accounts.asset_mint.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::set_authority(
+                freeze_ctx,
+                AuthorityType::FreezeAccount,
+                Some(new_signer),
+            )?;
+        }
+
+        // Every other token account the old signer owns (queued shares, basket
+        // components, stray reward tokens) is passed as a remaining account and
+        // follows it. Reward, emission and ve escrows belong to their own PDAs
+        let mut migrated = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut queued_shares: u64 = 0;
+        for account in ctx.remaining_accounts {
+            let token_account =
+                InterfaceAccount::<token_interface::TokenAccount>::try_from(account)?;
+            require!(
+                token_account.owner == old_signer,
+                ErrorCode::SignerAccountsNotMigrated
+            );
+            let token_program = [&ctx.accounts.token_program, &ctx.accounts.usdc_token_program]
+                .into_iter()
+                .find(|program| program.key() == *account.owner)
+                .ok_or(ErrorCode::SignerAccountsNotMigrated)?;
+
+            let owner_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token_interface::SetAuthority {
+                    current_authority: ctx.accounts.old_vault_signer.to_account_info(),
+                    account_or_mint: account.clone(),
+                },
+                signer,
+            );
+            token_interface::set_authority(
+                owner_ctx,
+                AuthorityType::AccountOwner,
+                Some(new_signer),
+            )?;
+
+            if token_account.mint == asset.mint {
+                queued_shares = queued_shares.checked_add(token_account.amount).unwrap();
+            }
+            migrated.push(account.key());
+        }
+
+        // Shares locked in the redeem queue and every basket component must
+        // have moved, or they would be stranded with the old signer
+        require!(
+            queued_shares >= vault.queued_redeem_shares,
+            ErrorCode::SignerAccountsNotMigrated
+        );
+        if vault.index_vault {
+            let basket = ctx
+                .accounts
+                .basket
+                .as_ref()
+                .ok_or(ErrorCode::SignerAccountsNotMigrated)?;
+            require!(
+                basket
+                    .components
+                    .iter()
+                    .all(|component| migrated.contains(&component.token_account)),
+                ErrorCode::SignerAccountsNotMigrated
+            );
+        }
+
+        vault.signer_version = new_version;
+        vault.signer_bump = ctx.bumps.new_vault_signer;
 
         Ok(())
     }
 
-    pub fn redeem(ctx: Context<Redeem>, amount: u64) -> Result<()> {
-        let asset = &ctx.accounts.asset;
-        let vault = &mut ctx.accounts.vault;
+    pub fn add_to_blacklist(ctx: Context<AddToBlacklist>, wallet: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
 
-        // Calculate USDC amount based on asset tokens and price
-        let usdc_amount = amount
-            .checked_mul(asset.price)
-            .unwrap()
-            .checked_div(1_000_000) // Convert from 6 decimals
-            .unwrap();
+        ctx.accounts.blacklist.wallet = wallet;
+        Ok(())
+    }
 
-        // Burn asset tokens
-        let burn_ctx = CpiContext::new(
+    pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn freeze_share_account(ctx: Context<FreezeShareAccount>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::NotBlacklisted
+        );
+
+        let vault = &ctx.accounts.vault;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let freeze_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Burn {
+            token_interface::FreezeAccount {
+                account: ctx.accounts.share_account.to_account_info(),
                 mint: ctx.accounts.asset_mint.to_account_info(),
-                from: ctx.accounts.user_asset_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
             },
+            signer,
+        );
+        token_interface::freeze_account(freeze_ctx)
+    }
+
+    pub fn thaw_share_account(ctx: Context<FreezeShareAccount>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
         );
-        token::burn(burn_ctx, amount)?;
 
-        // Transfer USDC from vault to user
+        let vault = &ctx.accounts.vault;
         let seeds = &[
-            b"vault".as_ref(),
-            asset.mint.as_ref(),
-            &[ctx.bumps.vault],
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
         ];
         let signer = &[&seeds[..]];
 
-        let transfer_ctx = CpiContext::new_with_signer(
+        let thaw_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_usdc_account.to_account_info(),
-                to: ctx.accounts.user_usdc_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
+            token_interface::ThawAccount {
+                account: ctx.accounts.share_account.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
             },
             signer,
         );
-        token::transfer(transfer_ctx, usdc_amount)?;
+        token_interface::thaw_account(thaw_ctx)
+    }
 
-        // Update vault state
-        vault.total_usdc = vault.total_usdc.checked_sub(usdc_amount).unwrap();
-        vault.total_assets = vault.total_assets.checked_sub(amount).unwrap();
+    // Legal hold on a holder's shares, independent of the blacklist
+    pub fn freeze_holder(ctx: Context<HolderFreeze>, reason: String) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(reason.len() <= MAX_REASON_LEN, ErrorCode::ReasonTooLong);
+
+        let vault = &ctx.accounts.vault;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let freeze_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::FreezeAccount {
+                account: ctx.accounts.share_account.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::freeze_account(freeze_ctx)?;
 
+        emit_event!(ctx.accounts, HolderFrozen {
+            vault: vault.key(),
+            share_account: ctx.accounts.share_account.key(),
+            owner: ctx.accounts.share_account.owner,
+            reason,
+        });
         Ok(())
     }
 
-    pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
-        let admin = &ctx.accounts.admin;
-        let vault = &mut ctx.accounts.vault;
-
-        // Verify admin authority
+    pub fn thaw_holder(ctx: Context<HolderFreeze>) -> Result<()> {
         require!(
-            admin.authority == ctx.accounts.authority.key(),
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
             ErrorCode::Unauthorized
         );
 
-        // Transfer USDC from vault to admin
+        let vault = &ctx.accounts.vault;
         let seeds = &[
-            b"vault".as_ref(),
+            b"vault_signer".as_ref(),
             ctx.accounts.asset.mint.as_ref(),
-            &[ctx.bumps.vault],
+            &[vault.signer_version],
+            &[vault.signer_bump],
         ];
         let signer = &[&seeds[..]];
 
-        let transfer_ctx = CpiContext::new_with_signer(
+        let thaw_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_usdc_account.to_account_info(),
-                to: ctx.accounts.admin_usdc_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
+            token_interface::ThawAccount {
+                account: ctx.accounts.share_account.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
             },
             signer,
         );
-        token::transfer(transfer_ctx, amount)?;
-
-        // Update vault state
-        vault.total_usdc = vault.total_usdc.checked_sub(amount).unwrap();
+        token_interface::thaw_account(thaw_ctx)?;
 
+        emit_event!(ctx.accounts, HolderThawed {
+            vault: vault.key(),
+            share_account: ctx.accounts.share_account.key(),
+            owner: ctx.accounts.share_account.owner,
+        });
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Admin::LEN,
-        seeds = [b"admin"],
-        bump
-    )]
-    pub admin: Account<'info, Admin>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    // Let a holder's share account carry confidential balances; only needed
+    // when the share mint doesn't auto-approve new accounts
+    pub fn approve_confidential_account(ctx: Context<ApproveConfidentialAccount>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
 
-#[derive(Accounts)]
-pub struct CreateAsset<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Asset::LEN,
-        seeds = [b"asset", mint.key().as_ref()],
-        bump
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(
+        let vault = &ctx.accounts.vault;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let ix = confidential_transfer::instruction::approve_account(
+            &Token2022::id(),
+            &ctx.accounts.share_account.key(),
+            &ctx.accounts.asset_mint.key(),
+            &ctx.accounts.vault_signer.key(),
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.share_account.to_account_info(),
+                ctx.accounts.asset_mint.to_account_info(),
+                ctx.accounts.vault_signer.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        Ok(())
+    }
+
+    // Switch auto-approval and rotate or clear the auditor key that can
+    // decrypt confidential transfer amounts
+    pub fn configure_confidential_mint(
+        ctx: Context<ConfigureConfidentialMint>,
+        auto_approve: bool,
+        auditor: Option<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Compliance,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let vault = &ctx.accounts.vault;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let ix = confidential_transfer::instruction::update_mint(
+            &Token2022::id(),
+            &ctx.accounts.asset_mint.key(),
+            &ctx.accounts.vault_signer.key(),
+            &[],
+            auto_approve,
+            auditor.map(PodElGamalPubkey::from),
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.asset_mint.to_account_info(),
+                ctx.accounts.vault_signer.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        Ok(())
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        creation_fee: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.creation_fee = creation_fee;
+        config.treasury = treasury;
+        Ok(())
+    }
+
+    // The registry is too large to create through CPI, so the client
+    // allocates it (zeroed, owned by this program) in the same transaction
+    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.registry == Pubkey::default(),
+            ErrorCode::InvalidRegistry
+        );
+
+        ctx.accounts.registry.load_init()?;
+        ctx.accounts.config.registry = ctx.accounts.registry.key();
+        Ok(())
+    }
+
+    // Page through the registry in creation order; meant to be simulated
+    // and read back as return data
+    pub fn list_assets(
+        ctx: Context<ListAssets>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<RegistryEntry>> {
+        let registry = ctx.accounts.registry.load()?;
+        Ok(registry.page(offset, limit.min(MAX_REGISTRY_PAGE)).to_vec())
+    }
+
+    pub fn set_creation_fee(
+        ctx: Context<UpdateConfig>,
+        creation_fee: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        let config = &mut ctx.accounts.config;
+        emit_event!(ctx.accounts, CreationFeeUpdated {
+            old_fee: config.creation_fee,
+            new_fee: creation_fee,
+            old_treasury: config.treasury,
+            new_treasury: treasury,
+        });
+        config.creation_fee = creation_fee;
+        config.treasury = treasury;
+        Ok(())
+    }
+
+    pub fn set_min_initial_deposit(
+        ctx: Context<UpdateConfig>,
+        min_initial_deposit: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts.config.min_initial_deposit = min_initial_deposit;
+        Ok(())
+    }
+
+    // Lamports every new listing must escrow; existing bonds keep the
+    // amount they were posted with
+    pub fn set_listing_bond(ctx: Context<UpdateConfig>, listing_bond: u64) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts.config.listing_bond = listing_bond;
+        Ok(())
+    }
+
+    // Reward tokens per second shared across all gauges by vote weight
+    pub fn set_gauge_emission_rate(
+        ctx: Context<UpdateConfig>,
+        gauge_emission_rate: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts.config.gauge_emission_rate = gauge_emission_rate;
+        Ok(())
+    }
+
+    pub fn set_global_pause(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        // Pausers and the guardian may pause but never unpause
+        let admin = &ctx.accounts.admin;
+        let signer = ctx.accounts.authority.key();
+        let role_grant = ctx.accounts.role_grant.as_deref();
+        let allowed = if paused {
+            admin.guardian == signer || admin.has_role(signer, Role::Pauser, role_grant)
+        } else {
+            admin.has_role(signer, Role::SuperAdmin, role_grant)
+        };
+        require!(allowed, ErrorCode::Unauthorized);
+
+        emit_event!(ctx.accounts, GlobalPauseUpdated {
+            old_paused: ctx.accounts.config.global_pause,
+            new_paused: paused,
+        });
+        ctx.accounts.config.global_pause = paused;
+        Ok(())
+    }
+
+    // The share mint is created here rather than supplied, so its authority
+    // and decimals always match the vault. It's a PDA of the asset, so the
+    // mint address follows from the vault rather than its ticker. The
+    // asset and vault are keyed by the underlying mint and a caller-chosen
+    // vault_id, so one underlying can back several vaults with their own
+    // fees and access rules. Token-2022 share mints get a metadata pointer
+    // to themselves and can opt into confidential transfers, the compliance
+    // transfer hook, or be non-transferable so positions only move through
+    // deposit and redeem.
+    // Anyone can list an asset by posting the configured bond, which the
+    // protocol admin can slash for a malicious listing
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_asset(
+        ctx: Context<CreateAsset>,
+        vault_id: u64,
+        name: String,
+        ticker: String,
+        price: u64,
+        deposit_limit: u64,
+        share_config: ShareMintConfig,
+        category: AssetCategory,
+        tags: u16,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+
+        let asset_key = ctx.accounts.asset.key();
+        let mint_seeds = &[b"share_mint".as_ref(), asset_key.as_ref(), &[ctx.bumps.mint]];
+        create_share_mint(
+            &ctx.accounts.mint.to_account_info(),
+            ctx.accounts.vault_signer.key(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &share_config,
+            &[&mint_seeds[..]],
+        )?;
+        charge_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        init_asset(
+            &mut ctx.accounts.asset,
+            &mut ctx.accounts.vault,
+            AssetParams {
+                vault_id,
+                name,
+                ticker,
+                price,
+                deposit_limit,
+                share_config,
+                category,
+                tags,
+            },
+            ctx.accounts.mint.key(),
+            ctx.accounts.authority.key(),
+            ctx.accounts.admin.signer_version,
+            ctx.bumps.vault_signer,
+            &ctx.accounts.underlying_mint,
+        )?;
+        ctx.accounts.ticker_claim.asset = ctx.accounts.asset.key();
+        ctx.accounts.stats.asset = ctx.accounts.asset.key();
+        post_bond(
+            &mut ctx.accounts.bond,
+            ctx.accounts.asset.key(),
+            &ctx.accounts.config,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ctx.accounts
+            .registry
+            .load_mut()?
+            .push(ctx.accounts.asset.key(), &ctx.accounts.asset)
+    }
+
+    // Onboard a catalog of assets over one underlying in a single
+    // transaction. Each asset passes [asset, vault, vault_signer, mint,
+    // ticker_claim, bond, stats] as remaining accounts, with the mint at
+    // its ticker PDA as in create_asset
+    pub fn batch_create_assets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchCreateAssets<'info>>,
+        assets: Vec<AssetParams>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            !assets.is_empty()
+                && assets.len() <= MAX_BATCH_ASSETS
+                && ctx.remaining_accounts.len() == assets.len() * BATCH_ASSET_ACCOUNTS,
+            ErrorCode::InvalidBatch
+        );
+
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let signer_version = ctx.accounts.admin.signer_version;
+        let underlying = ctx.accounts.underlying_mint.key();
+        let legs = ctx.remaining_accounts.chunks(BATCH_ASSET_ACCOUNTS);
+        for (params, leg) in assets.into_iter().zip(legs) {
+            let (asset_info, vault_info, vault_signer, mint, ticker_claim, bond_info, stats_info) =
+                (&leg[0], &leg[1], &leg[2], &leg[3], &leg[4], &leg[5], &leg[6]);
+            let ticker = normalize_ticker(&params.ticker);
+
+            let vault_id = params.vault_id.to_le_bytes();
+
+            let (asset_key, asset_bump) = Pubkey::find_program_address(
+                &[b"asset", underlying.as_ref(), vault_id.as_ref()],
+                &crate::ID,
+            );
+            let (mint_key, mint_bump) =
+                Pubkey::find_program_address(&[b"share_mint", asset_key.as_ref()], &crate::ID);
+            let (vault_key, vault_bump) = Pubkey::find_program_address(
+                &[b"vault", underlying.as_ref(), vault_id.as_ref()],
+                &crate::ID,
+            );
+            let (signer_key, signer_bump) = Pubkey::find_program_address(
+                &[b"vault_signer", mint.key.as_ref(), &[signer_version]],
+                &crate::ID,
+            );
+            let (claim_key, claim_bump) =
+                Pubkey::find_program_address(&[b"ticker", ticker.as_bytes()], &crate::ID);
+            let (bond_key, bond_bump) =
+                Pubkey::find_program_address(&[b"bond", asset_key.as_ref()], &crate::ID);
+            let (stats_key, stats_bump) =
+                Pubkey::find_program_address(&[b"stats", asset_key.as_ref()], &crate::ID);
+            require!(
+                mint.key() == mint_key
+                    && asset_info.key() == asset_key
+                    && vault_info.key() == vault_key
+                    && vault_signer.key() == signer_key
+                    && ticker_claim.key() == claim_key
+                    && bond_info.key() == bond_key
+                    && stats_info.key() == stats_key,
+                ErrorCode::InvalidBatch
+            );
+
+            let mint_seeds = &[b"share_mint".as_ref(), asset_key.as_ref(), &[mint_bump]];
+            create_share_mint(
+                mint,
+                signer_key,
+                &authority,
+                &system_program,
+                &token_program,
+                &params.share_config,
+                &[&mint_seeds[..]],
+            )?;
+            charge_creation_fee(
+                &ctx.accounts.config,
+                &authority,
+                &ctx.accounts.treasury.to_account_info(),
+                &system_program,
+            )?;
+
+            // A taken vault id fails here, as the asset account already exists
+            let asset_seeds = [b"asset".as_ref(), underlying.as_ref(), &vault_id, &[asset_bump]];
+            let vault_seeds = [b"vault".as_ref(), underlying.as_ref(), &vault_id, &[vault_bump]];
+            let claim_seeds = [b"ticker".as_ref(), ticker.as_bytes(), &[claim_bump]];
+            let bond_seeds = [b"bond".as_ref(), asset_key.as_ref(), &[bond_bump]];
+            let stats_seeds = [b"stats".as_ref(), asset_key.as_ref(), &[stats_bump]];
+            for (info, seeds, space) in [
+                (asset_info, &asset_seeds[..], 8 + Asset::LEN),
+                (vault_info, &vault_seeds[..], 8 + Vault::LEN),
+                (ticker_claim, &claim_seeds[..], 8 + TickerClaim::LEN),
+                (bond_info, &bond_seeds[..], 8 + Bond::LEN),
+                (stats_info, &stats_seeds[..], 8 + Stats::LEN),
+            ] {
+                let signer = &[seeds];
+                let create_ctx = CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::CreateAccount {
+                        from: authority.clone(),
+                        to: info.clone(),
+                    },
+                    signer,
+                );
+                system_program::create_account(
+                    create_ctx,
+                    Rent::get()?.minimum_balance(space),
+                    space as u64,
+                    &crate::ID,
+                )?;
+            }
+
+            // Freshly allocated accounts are zeroed, which deserializes as
+            // empty accounts; exit writes the discriminators
+            let mut asset = Account::<Asset>::try_from_unchecked(asset_info)?;
+            let mut vault = Account::<Vault>::try_from_unchecked(vault_info)?;
+            let mut claim = Account::<TickerClaim>::try_from_unchecked(ticker_claim)?;
+            let mut bond = Account::<Bond>::try_from_unchecked(bond_info)?;
+            let mut stats = Account::<Stats>::try_from_unchecked(stats_info)?;
+            init_asset(
+                &mut asset,
+                &mut vault,
+                params,
+                mint.key(),
+                authority.key(),
+                signer_version,
+                signer_bump,
+                &ctx.accounts.underlying_mint,
+            )?;
+            claim.asset = asset.key();
+            stats.asset = asset.key();
+            post_bond(
+                &mut bond,
+                asset.key(),
+                &ctx.accounts.config,
+                &authority,
+                &system_program,
+            )?;
+            ctx.accounts.registry.load_mut()?.push(asset.key(), &asset)?;
+            asset.exit(&crate::ID)?;
+            vault.exit(&crate::ID)?;
+            claim.exit(&crate::ID)?;
+            bond.exit(&crate::ID)?;
+            stats.exit(&crate::ID)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let source = ctx
+            .accounts
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .to_account_info();
+        deposit_underlying(ctx.accounts, source, amount, &proof, None)
+    }
+
+    // Same as deposit, with a caller reference (e.g. an exchange's internal
+    // transfer id) carried on the DepositEvent
+    pub fn deposit_with_memo(
+        ctx: Context<Deposit>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        memo: String,
+    ) -> Result<()> {
+        require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        let source = ctx
+            .accounts
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .to_account_info();
+        deposit_underlying(ctx.accounts, source, amount, &proof, Some(memo))
+    }
+
+    // Deposit native SOL into a wSOL vault: the lamports are wrapped into a
+    // wSOL account at a PDA of the user, deposited as usual, and the emptied
+    // account is closed back to the user, so their own token accounts are
+    // never touched
+    pub fn deposit_sol(ctx: Context<DepositSol>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        require!(
+            ctx.accounts.deposit.vault.underlying_mint == token::spl_token::native_mint::ID,
+            ErrorCode::NotNativeVault
+        );
+
+        let user_key = ctx.accounts.deposit.user.key();
+        let seeds = &[b"wsol".as_ref(), user_key.as_ref(), &[ctx.bumps.wsol_account]];
+        let wsol_account = ctx.accounts.wsol_account.to_account_info();
+        open_wsol_account(
+            &wsol_account,
+            &ctx.accounts.deposit.user.to_account_info(),
+            &ctx.accounts.deposit.usdc_mint.to_account_info(),
+            &ctx.accounts.deposit.system_program.to_account_info(),
+            &ctx.accounts.deposit.usdc_token_program.to_account_info(),
+            amount,
+            &[&seeds[..]],
+        )?;
+
+        deposit_underlying(&mut ctx.accounts.deposit, wsol_account.clone(), amount, &proof, None)?;
+
+        let close_ctx = CpiContext::new(
+            ctx.accounts.deposit.usdc_token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: wsol_account,
+                destination: ctx.accounts.deposit.user.to_account_info(),
+                authority: ctx.accounts.deposit.user.to_account_info(),
+            },
+        );
+        token_interface::close_account(close_ctx)
+    }
+
+    // Split one USDC amount across several vaults by basis-point weights.
+    // Each leg passes [asset, vault, vault_signer, asset_mint,
+    // vault_usdc_account, user_asset_account, stats] as remaining accounts. Vaults
+    // with per-wallet limits, allowlists or credentials need a regular deposit
+    pub fn batch_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchDeposit<'info>>,
+        amount: u64,
+        weights: Vec<u16>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+        require!(
+            !weights.is_empty()
+                && weights.len() <= MAX_BATCH_LEGS
+                && ctx.remaining_accounts.len() == weights.len() * BATCH_LEG_ACCOUNTS
+                && weights.iter().map(|weight| *weight as u64).sum::<u64>() == BPS_DENOMINATOR,
+            ErrorCode::InvalidBatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let slot = Clock::get()?.slot;
+        let mut remaining = amount;
+        let legs = ctx.remaining_accounts.chunks(BATCH_LEG_ACCOUNTS);
+        for (index, (weight, leg)) in weights.iter().zip(legs).enumerate() {
+            // The last leg takes the rounding remainder
+            let leg_amount = if index == weights.len() - 1 {
+                remaining
+            } else {
+                bps_of(amount, *weight)
+            };
+            remaining = remaining.checked_sub(leg_amount).unwrap();
+
+            let asset = Account::<Asset>::try_from(&leg[0])?;
+            let mut vault = Account::<Vault>::try_from(&leg[1])?;
+            let vault_signer = &leg[2];
+            let asset_mint = &leg[3];
+            let vault_usdc_account =
+                InterfaceAccount::<token_interface::TokenAccount>::try_from(&leg[4])?;
+            let user_asset_account = &leg[5];
+            let mut stats = Account::<Stats>::try_from(&leg[6])?;
+
+            let seeds = &[
+                b"vault_signer".as_ref(),
+                asset.mint.as_ref(),
+                &[vault.signer_version],
+                &[vault.signer_bump],
+            ];
+            let signer = &[&seeds[..]];
+            let signer_key = Pubkey::create_program_address(&seeds[..], &crate::ID)
+                .map_err(|_| error!(ErrorCode::InvalidBatch))?;
+            require!(
+                asset.vault == vault.key()
+                    && asset_mint.key() == asset.mint
+                    && asset_mint.owner == &ctx.accounts.token_program.key()
+                    && vault_signer.key() == signer_key
+                    && vault.underlying_mint == ctx.accounts.usdc_mint.key()
+                    && vault_usdc_account.mint == vault.underlying_mint
+                    && vault_usdc_account.owner == signer_key
+                    && stats.asset == asset.key(),
+                ErrorCode::InvalidBatch
+            );
+
+            require!(!vault.shutdown, ErrorCode::VaultShutdown);
+            require!(!asset.delisted, ErrorCode::AssetDelisted);
+            require!(
+                vault.pause_flags & PAUSE_DEPOSITS == 0,
+                ErrorCode::DepositsPaused
+            );
+            require!(
+                vault.signer_version == ctx.accounts.admin.signer_version,
+                ErrorCode::VaultSignerOutdated
+            );
+            require!(
+                !vault.allowlist_enabled
+                    && vault.credential_mint == Pubkey::default()
+                    && vault.per_user_cap == 0
+                    && vault.redeem_cooldown_slots == 0,
+                ErrorCode::BatchLegRestricted
+            );
+            require!(leg_amount >= vault.min_deposit, ErrorCode::DepositTooSmall);
+            if vault.total_assets == 0 && vault.fee_shares_outstanding == 0 {
+                require!(
+                    leg_amount >= ctx.accounts.config.min_initial_deposit,
+                    ErrorCode::InitialDepositTooSmall
+                );
+            }
+            vault.check_basket_fresh(now)?;
+
+            let fee = bps_of(leg_amount, vault.deposit_fee_bps);
+            let withheld = transfer_fee(&ctx.accounts.usdc_mint, leg_amount)?;
+            let net_amount = leg_amount.checked_sub(fee).unwrap().checked_sub(withheld).unwrap();
+            let backing = match vault.fee_mode {
+                FeeMode::Assets => net_amount,
+                FeeMode::Shares => net_amount.checked_add(fee).unwrap(),
+            };
+
+            vault.record_inflow(leg_amount, slot)?;
+            let new_total = vault.total_usdc.checked_add(backing).unwrap();
+            require!(
+                new_total.checked_add(vault.quote_backing).unwrap() <= vault.deposit_limit,
+                ErrorCode::DepositLimitExceeded
+            );
+            let asset_amount = vault.to_shares(net_amount, asset.price);
+            require!(asset_amount > 0, ErrorCode::ZeroShares);
+
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.usdc_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_usdc_account.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: vault_usdc_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            );
+            token_interface::transfer_checked(
+                transfer_ctx,
+                leg_amount,
+                ctx.accounts.usdc_mint.decimals,
+            )?;
+
+            let mint_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: asset_mint.clone(),
+                    to: user_asset_account.clone(),
+                    authority: vault_signer.clone(),
+                },
+                signer,
+            );
+            token_interface::mint_to(mint_ctx, asset_amount)?;
+
+            vault.total_usdc = new_total;
+            vault.total_assets = vault.total_assets.checked_add(asset_amount).unwrap();
+            match vault.fee_mode {
+                FeeMode::Assets => {
+                    vault.fees_outstanding = vault.fees_outstanding.checked_add(fee).unwrap();
+                }
+                FeeMode::Shares => {
+                    let fee_shares = vault.to_shares(fee, asset.price);
+                    vault.fee_shares_outstanding =
+                        vault.fee_shares_outstanding.checked_add(fee_shares).unwrap();
+                }
+            }
+            stats.record_deposit(leg_amount, vault.total_backing());
+            vault.exit(&crate::ID)?;
+            stats.exit(&crate::ID)?;
+
+            emit_event!(ctx.accounts, DepositEvent {
+                user: ctx.accounts.user.key(),
+                asset: asset.key(),
+                usdc_in: leg_amount,
+                shares_out: asset_amount,
+                price: asset.price,
+                memo: None,
+            });
+        }
+
+        emit_event!(ctx.accounts, BatchDeposited {
+            user: ctx.accounts.user.key(),
+            amount,
+            legs: weights.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    // Zap in: swap `amount_in` of any token into the underlying through a
+    // whitelisted aggregator route, then deposit what arrived. `data` is the
+    // route instruction and the remaining accounts are its accounts; only
+    // the user signs the swap, never the vault signer
+    pub fn deposit_with_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositWithSwap<'info>>,
+        amount_in: u64,
+        min_out: u64,
+        data: Vec<u8>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let input_before = ctx.accounts.user_input_account.amount;
+        let usdc_before = ctx
+            .accounts
+            .deposit
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .amount;
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                if account.is_writable {
+                    AccountMeta::new(account.key(), account.is_signer)
+                } else {
+                    AccountMeta::new_readonly(account.key(), account.is_signer)
+                }
+            })
+            .collect();
+        let mut infos = vec![ctx.accounts.router_program.to_account_info()];
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+        invoke(
+            &Instruction {
+                program_id: ctx.accounts.router_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+        )?;
+
+        ctx.accounts.user_input_account.reload()?;
+        let user_usdc_account = ctx
+            .accounts
+            .deposit
+            .user_usdc_account
+            .as_mut()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?;
+        user_usdc_account.reload()?;
+        let source = user_usdc_account.to_account_info();
+        let spent = input_before.saturating_sub(ctx.accounts.user_input_account.amount);
+        let received = user_usdc_account.amount.saturating_sub(usdc_before);
+        require!(spent <= amount_in, ErrorCode::InvalidRouterAccounts);
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+
+        deposit_underlying(&mut ctx.accounts.deposit, source, received, &proof, None)?;
+
+        emit_event!(ctx.accounts.deposit, SwapDeposited {
+            vault: ctx.accounts.deposit.vault.key(),
+            user: ctx.accounts.deposit.user.key(),
+            input_mint: ctx.accounts.user_input_account.mint,
+            amount_in: spent,
+            usdc_out: received,
+        });
+
+        Ok(())
+    }
+
+    // Let the vault take deposits in another stablecoin; quote tokens sit in
+    // their own vault account and back shares at their haircut value
+    pub fn add_quote_mint(ctx: Context<AddQuoteMint>, haircut_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(haircut_bps <= MAX_HAIRCUT_BPS, ErrorCode::InvalidHaircut);
+        require!(
+            ctx.accounts.quote_mint.key() != ctx.accounts.vault.underlying_mint,
+            ErrorCode::InvalidQuoteMint
+        );
+
+        let quote = &mut ctx.accounts.quote;
+        quote.vault = ctx.accounts.vault.key();
+        quote.mint = ctx.accounts.quote_mint.key();
+        quote.token_account = ctx.accounts.vault_quote_account.key();
+        quote.decimals = ctx.accounts.quote_mint.decimals;
+        quote.haircut_bps = haircut_bps;
+        quote.enabled = true;
+        quote.balance = 0;
+        Ok(())
+    }
+
+    // Changing the haircut re-marks the quote tokens already held, so share
+    // pricing moves with it
+    pub fn update_quote_mint(
+        ctx: Context<UpdateQuoteMint>,
+        haircut_bps: u16,
+        enabled: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(haircut_bps <= MAX_HAIRCUT_BPS, ErrorCode::InvalidHaircut);
+
+        let vault = &mut ctx.accounts.vault;
+        let quote = &mut ctx.accounts.quote;
+        let value_before = quote.value(vault.underlying_decimals);
+        quote.haircut_bps = haircut_bps;
+        quote.enabled = enabled;
+        vault.quote_backing = vault
+            .quote_backing
+            .checked_sub(value_before)
+            .unwrap()
+            .checked_add(quote.value(vault.underlying_decimals))
+            .unwrap();
+        Ok(())
+    }
+
+    // Open a share class over the vault's pool: a second share mint priced
+    // at the same NAV, whose holders pay the class's own fees and must wait
+    // out its lockup (in slots since their last deposit) before redeeming.
+    // The class mint is a PDA of the vault and a caller-chosen class_id
+    pub fn add_share_class(
+        ctx: Context<AddShareClass>,
+        class_id: u8,
+        deposit_fee_bps: u16,
+        redeem_fee_bps: u16,
+        lockup_slots: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            deposit_fee_bps <= MAX_FEE_BPS && redeem_fee_bps <= MAX_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+
+        let share_config = ShareMintConfig {
+            decimals: ctx.accounts.vault.share_decimals,
+            confidential: false,
+            auto_approve: false,
+            auditor: None,
+            transfer_hook: false,
+            non_transferable: false,
+        };
+        let vault_key = ctx.accounts.vault.key();
+        let mint_seeds = &[
+            b"share_class_mint".as_ref(),
+            vault_key.as_ref(),
+            &[class_id],
+            &[ctx.bumps.class_mint],
+        ];
+        create_share_mint(
+            &ctx.accounts.class_mint.to_account_info(),
+            ctx.accounts.vault_signer.key(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &share_config,
+            &[&mint_seeds[..]],
+        )?;
+
+        let class = &mut ctx.accounts.share_class;
+        class.vault = ctx.accounts.vault.key();
+        class.mint = ctx.accounts.class_mint.key();
+        class.deposit_fee_bps = deposit_fee_bps;
+        class.redeem_fee_bps = redeem_fee_bps;
+        class.lockup_slots = lockup_slots;
+        class.supply = 0;
+        Ok(())
+    }
+
+    // Deposit one of the vault's quote mints. Shares are minted against the
+    // amount received, converted to underlying units and less the haircut
+    pub fn deposit_quote(
+        ctx: Context<DepositQuote>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        let quote = &mut ctx.accounts.quote;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(!vault.shutdown, ErrorCode::VaultShutdown);
+        require!(!asset.delisted, ErrorCode::AssetDelisted);
+        require!(
+            vault.pause_flags & PAUSE_DEPOSITS == 0,
+            ErrorCode::DepositsPaused
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(quote.enabled, ErrorCode::QuoteDisabled);
+        vault.check_basket_fresh(Clock::get()?.unix_timestamp)?;
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+        if vault.allowlist_enabled {
+            require!(
+                ctx.accounts.allowlist_entry.is_some()
+                    || vault.allowlist_contains(ctx.accounts.user.key(), &proof),
+                ErrorCode::NotAllowlisted
+            );
+        }
+        if vault.credential_mint != Pubkey::default() {
+            let credential = ctx
+                .accounts
+                .credential
+                .as_ref()
+                .ok_or(ErrorCode::MissingCredential)?;
+            require!(
+                credential.mint == vault.credential_mint
+                    && credential.owner == ctx.accounts.user.key()
+                    && credential.amount > 0,
+                ErrorCode::MissingCredential
+            );
+        }
+
+        let received = amount
+            .checked_sub(transfer_fee(&ctx.accounts.quote_mint, amount)?)
+            .unwrap();
+        let value_before = quote.value(vault.underlying_decimals);
+        quote.balance = quote.balance.checked_add(received).unwrap();
+        let value = quote
+            .value(vault.underlying_decimals)
+            .checked_sub(value_before)
+            .unwrap();
+        require!(value >= vault.min_deposit, ErrorCode::DepositTooSmall);
+        if vault.total_assets == 0 && vault.fee_shares_outstanding == 0 {
+            require!(
+                value >= ctx.accounts.config.min_initial_deposit,
+                ErrorCode::InitialDepositTooSmall
+            );
+        }
+
+        let position = &mut ctx.accounts.position;
+        position.deposited = position.deposited.checked_add(value).unwrap();
+        position.last_deposit_slot = Clock::get()?.slot;
+        position.record_activity(Clock::get()?.slot);
+        require!(
+            vault.per_user_cap == 0 || position.deposited <= vault.per_user_cap,
+            ErrorCode::PerUserCapExceeded
+        );
+
+        vault.record_inflow(value, Clock::get()?.slot)?;
+
+        let quote_backing = vault.quote_backing.checked_add(value).unwrap();
+        require!(
+            vault.total_usdc.checked_add(quote_backing).unwrap() <= vault.deposit_limit,
+            ErrorCode::DepositLimitExceeded
+        );
+
+        // The fee can't be held aside in underlying here, so it is always
+        // taken as fee shares backed by the quote tokens
+        let fee = bps_of(value, vault.deposit_fee_bps);
+        let asset_amount = vault.to_shares(value.checked_sub(fee).unwrap(), asset.price);
+        require!(asset_amount > 0, ErrorCode::ZeroShares);
+        let fee_shares = vault.to_shares(fee, asset.price);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.quote_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_quote_account.to_account_info(),
+                mint: ctx.accounts.quote_mint.to_account_info(),
+                to: ctx.accounts.vault_quote_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(
+            transfer_ctx,
+            amount,
+            ctx.accounts.quote_mint.decimals,
+        )?;
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::MintTo {
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                to: ctx.accounts.user_asset_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::mint_to(mint_ctx, asset_amount)?;
+
+        if ctx.accounts.user_asset_account.key()
+            == get_associated_token_address_with_program_id(
+                &ctx.accounts.user.key(),
+                &asset.mint,
+                &ctx.accounts.token_program.key(),
+            )
+        {
+            ctx.accounts.user_asset_account.reload()?;
+            ctx.accounts.position.observe_balance(
+                ctx.accounts.user_asset_account.amount,
+                Clock::get()?.unix_timestamp,
+            );
+        }
+
+        vault.quote_backing = quote_backing;
+        vault.total_assets = vault.total_assets.checked_add(asset_amount).unwrap();
+        vault.fee_shares_outstanding =
+            vault.fee_shares_outstanding.checked_add(fee_shares).unwrap();
+        ctx.accounts.stats.record_deposit(value, vault.total_backing());
+
+        emit_event!(ctx.accounts, QuoteDeposited {
+            vault: vault.key(),
+            quote_mint: quote.mint,
+            user: ctx.accounts.user.key(),
+            amount,
+            value,
+            shares: asset_amount,
+        });
+        emit_event!(ctx.accounts, DepositEvent {
+            user: ctx.accounts.user.key(),
+            asset: asset.key(),
+            usdc_in: value,
+            shares_out: asset_amount,
+            price: asset.price,
+            memo: None,
+        });
+
+        Ok(())
+    }
+
+    // Swap quote tokens into the underlying through a whitelisted router so
+    // they can fund redemptions. Their haircut value leaves the quote backing
+    // and the proceeds join the vault's USDC
+    pub fn convert_quote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConvertQuote<'info>>,
+        amount: u64,
+        min_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        // Quote tokens must be converted before a rotated vault can migrate,
+        // so this also runs under an outdated signer
+        require!(
+            amount <= ctx.accounts.quote.balance,
+            ErrorCode::InsufficientQuoteBalance
+        );
+
+        // The vault signer signs the swap, so the router must not see any
+        // other token account it owns
+        let vault_signer = ctx.accounts.vault_signer.key();
+        let vault_quote_account = ctx.accounts.vault_quote_account.key();
+        let vault_usdc_account = ctx.accounts.vault_usdc_account.key();
+        for account in ctx.remaining_accounts {
+            if (account.owner == &token::ID || account.owner == &Token2022::id())
+                && account.data_len() >= TokenAccount::LEN
+            {
+                let owner = Pubkey::new_from_array(
+                    account.try_borrow_data()?[32..64].try_into().unwrap(),
+                );
+                require!(
+                    owner != vault_signer
+                        || account.key() == vault_quote_account
+                        || account.key() == vault_usdc_account,
+                    ErrorCode::InvalidRouterAccounts
+                );
+            }
+        }
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                let is_signer = account.is_signer || account.key() == vault_signer;
+                if account.is_writable {
+                    AccountMeta::new(account.key(), is_signer)
+                } else {
+                    AccountMeta::new_readonly(account.key(), is_signer)
+                }
+            })
+            .collect();
+        let mut infos = vec![
+            ctx.accounts.router_program.to_account_info(),
+            ctx.accounts.vault_signer.to_account_info(),
+        ];
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let quote_before = ctx.accounts.vault_quote_account.amount;
+        let usdc_before = ctx.accounts.vault_usdc_account.amount;
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.router_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.vault_quote_account.reload()?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        // The router ran with the vault signer's authority, so it must not
+        // have left a delegate or close authority on the vault's accounts
+        for account in [&ctx.accounts.vault_quote_account, &ctx.accounts.vault_usdc_account] {
+            require!(
+                account.delegate.is_none() && account.close_authority.is_none(),
+                ErrorCode::InvalidRouterAccounts
+            );
+        }
+        let spent = quote_before.saturating_sub(ctx.accounts.vault_quote_account.amount);
+        let received = ctx.accounts.vault_usdc_account.amount.saturating_sub(usdc_before);
+        require!(
+            spent <= amount && ctx.accounts.vault_usdc_account.amount >= usdc_before,
+            ErrorCode::InvalidRouterAccounts
+        );
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+
+        let vault = &mut ctx.accounts.vault;
+        let quote = &mut ctx.accounts.quote;
+        let value_before = quote.value(vault.underlying_decimals);
+        quote.balance = quote.balance.checked_sub(spent).unwrap();
+        let released = value_before
+            .checked_sub(quote.value(vault.underlying_decimals))
+            .unwrap();
+        vault.quote_backing = vault.quote_backing.checked_sub(released).unwrap();
+        vault.total_usdc = vault.total_usdc.checked_add(received).unwrap();
+
+        emit_event!(ctx.accounts, QuoteConverted {
+            vault: vault.key(),
+            quote_mint: quote.mint,
+            amount_in: spent,
+            usdc_out: received,
+            released,
+        });
+
+        Ok(())
+    }
+
+    pub fn redeem(ctx: Context<Redeem>, amount: u64) -> Result<()> {
+        let destination = ctx
+            .accounts
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .to_account_info();
+        redeem_shares(ctx.accounts, destination, amount, None)
+    }
+
+    // Same as redeem, with a caller reference carried on the RedeemEvent
+    pub fn redeem_with_memo(ctx: Context<Redeem>, amount: u64, memo: String) -> Result<()> {
+        require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        let destination = ctx
+            .accounts
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .to_account_info();
+        redeem_shares(ctx.accounts, destination, amount, Some(memo))
+    }
+
+    // Redeem from a wSOL vault and unwrap: the payout lands in a wSOL account
+    // at a PDA of the user, which is then closed so the user receives plain
+    // SOL along with the account's rent
+    pub fn redeem_to_sol(ctx: Context<RedeemToSol>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.redeem.vault.underlying_mint == token::spl_token::native_mint::ID,
+            ErrorCode::NotNativeVault
+        );
+
+        let user_key = ctx.accounts.redeem.user.key();
+        let seeds = &[b"wsol".as_ref(), user_key.as_ref(), &[ctx.bumps.wsol_account]];
+        let wsol_account = ctx.accounts.wsol_account.to_account_info();
+        open_wsol_account(
+            &wsol_account,
+            &ctx.accounts.redeem.user.to_account_info(),
+            &ctx.accounts.redeem.usdc_mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.redeem.usdc_token_program.to_account_info(),
+            0,
+            &[&seeds[..]],
+        )?;
+
+        redeem_shares(&mut ctx.accounts.redeem, wsol_account.clone(), amount, None)?;
+
+        let close_ctx = CpiContext::new(
+            ctx.accounts.redeem.usdc_token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: wsol_account,
+                destination: ctx.accounts.redeem.user.to_account_info(),
+                authority: ctx.accounts.redeem.user.to_account_info(),
+            },
+        );
+        token_interface::close_account(close_ctx)
+    }
+
+    // Zap out: redeem into the user's USDC account, then route the payout
+    // into the requested output token through a whitelisted aggregator.
+    // As with zap-in, only the user signs the swap
+    pub fn redeem_with_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RedeemWithSwap<'info>>,
+        amount: u64,
+        min_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let user_usdc_account = ctx
+            .accounts
+            .redeem
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?;
+        let usdc_before = user_usdc_account.amount;
+        let destination = user_usdc_account.to_account_info();
+        redeem_shares(&mut ctx.accounts.redeem, destination, amount, None)?;
+        let user_usdc_account = ctx.accounts.redeem.user_usdc_account.as_mut().unwrap();
+        user_usdc_account.reload()?;
+        let payout = user_usdc_account.amount.checked_sub(usdc_before).unwrap();
+        let usdc_before = user_usdc_account.amount;
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                if account.is_writable {
+                    AccountMeta::new(account.key(), account.is_signer)
+                } else {
+                    AccountMeta::new_readonly(account.key(), account.is_signer)
+                }
+            })
+            .collect();
+        let mut infos = vec![ctx.accounts.router_program.to_account_info()];
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+
+        let output_before = ctx.accounts.user_output_account.amount;
+        invoke(
+            &Instruction {
+                program_id: ctx.accounts.router_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+        )?;
+
+        let user_usdc_account = ctx.accounts.redeem.user_usdc_account.as_mut().unwrap();
+        user_usdc_account.reload()?;
+        let spent = usdc_before.saturating_sub(user_usdc_account.amount);
+        ctx.accounts.user_output_account.reload()?;
+        let received = ctx
+            .accounts
+            .user_output_account
+            .amount
+            .saturating_sub(output_before);
+        require!(spent <= payout, ErrorCode::InvalidRouterAccounts);
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+
+        emit_event!(ctx.accounts.redeem, SwapRedeemed {
+            vault: ctx.accounts.redeem.vault.key(),
+            user: ctx.accounts.redeem.user.key(),
+            output_mint: ctx.accounts.user_output_account.mint,
+            usdc_in: spent,
+            amount_out: received,
+        });
+
+        Ok(())
+    }
+
+    // Rotate from one vault into another in a single step: shares of the
+    // first are burned at its price and shares of the second minted at its
+    // price, with the USDC moving vault to vault. Only the exit's redemption
+    // fee applies; otherwise the exit faces the same checks as a redeem and
+    // the entry the same checks as a deposit
+    pub fn swap_assets(
+        ctx: Context<SwapAssets>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let amount = ctx
+            .accounts
+            .from_vault
+            .dust_adjusted(amount, ctx.accounts.user_from_account.amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        let slot = Clock::get()?.slot;
+        let from_asset = &ctx.accounts.from_asset;
+        let to_asset = &ctx.accounts.to_asset;
+        let from_vault = &mut ctx.accounts.from_vault;
+        let to_vault = &mut ctx.accounts.to_vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            from_vault.pause_flags & PAUSE_REDEMPTIONS == 0,
+            ErrorCode::RedemptionsPaused
+        );
+        require!(
+            !from_asset.delisted || now <= from_asset.settlement_deadline,
+            ErrorCode::SettlementWindowClosed
+        );
+        require!(!to_vault.shutdown, ErrorCode::VaultShutdown);
+        require!(!to_asset.delisted, ErrorCode::AssetDelisted);
+        require!(
+            to_vault.pause_flags & PAUSE_DEPOSITS == 0,
+            ErrorCode::DepositsPaused
+        );
+        require!(
+            from_vault.signer_version == ctx.accounts.admin.signer_version
+                && to_vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+        if to_vault.allowlist_enabled {
+            require!(
+                ctx.accounts.allowlist_entry.is_some()
+                    || to_vault.allowlist_contains(ctx.accounts.user.key(), &proof),
+                ErrorCode::NotAllowlisted
+            );
+        }
+        if to_vault.credential_mint != Pubkey::default() {
+            let credential = ctx
+                .accounts
+                .credential
+                .as_ref()
+                .ok_or(ErrorCode::MissingCredential)?;
+            require!(
+                credential.mint == to_vault.credential_mint
+                    && credential.owner == ctx.accounts.user.key()
+                    && credential.amount > 0,
+                ErrorCode::MissingCredential
+            );
+        }
+        from_vault.check_basket_fresh(now)?;
+        to_vault.check_basket_fresh(now)?;
+
+        // The exit is a redemption, so the same-transaction and same-slot
+        // guards and the holding period still apply
+        let own_index = load_current_index_checked(&ctx.accounts.instructions)? as usize;
+        check_no_deposit_in_transaction(&ctx.accounts.instructions, Some(own_index))?;
+        let position = &ctx.accounts.from_position;
+        require!(position.last_deposit_slot != slot, ErrorCode::SameSlotRedeem);
+        if from_vault.redeem_cooldown_slots > 0 {
+            let unlock_slot = position
+                .last_deposit_slot
+                .checked_add(from_vault.redeem_cooldown_slots)
+                .unwrap();
+            require!(slot >= unlock_slot, ErrorCode::RedeemCooldownActive);
+        }
+
+        let usdc_amount = from_vault.redemption_value(amount, from_asset.price, now);
+        let fee = bps_of(usdc_amount, from_vault.redeem_fee_bps);
+        let moved = usdc_amount.checked_sub(fee).unwrap();
+        from_vault.consume_redeem_capacity(moved, now)?;
+
+        // The entry is a deposit of whatever reaches the second vault
+        let received = moved
+            .checked_sub(transfer_fee(&ctx.accounts.usdc_mint, moved)?)
+            .unwrap();
+        require!(received >= to_vault.min_deposit, ErrorCode::DepositTooSmall);
+        if to_vault.total_assets == 0 && to_vault.fee_shares_outstanding == 0 {
+            require!(
+                received >= ctx.accounts.config.min_initial_deposit,
+                ErrorCode::InitialDepositTooSmall
+            );
+        }
+        to_vault.record_inflow(received, slot)?;
+        let new_total = to_vault.total_usdc.checked_add(received).unwrap();
+        require!(
+            new_total.checked_add(to_vault.quote_backing).unwrap() <= to_vault.deposit_limit,
+            ErrorCode::DepositLimitExceeded
+        );
+        let shares = to_vault.to_shares(received, to_asset.price);
+        require!(shares > 0, ErrorCode::ZeroShares);
+
+        // Both legs are recorded on the user's positions, the exit as a
+        // redemption of what reached the second vault
+        let position = &mut ctx.accounts.from_position;
+        position.redeemed = position.redeemed.checked_add(received).unwrap();
+        position.record_activity(slot);
+        let position = &mut ctx.accounts.to_position;
+        position.deposited = position.deposited.checked_add(received).unwrap();
+        position.last_deposit_slot = slot;
+        position.record_activity(slot);
+        require!(
+            to_vault.per_user_cap == 0 || position.deposited <= to_vault.per_user_cap,
+            ErrorCode::PerUserCapExceeded
+        );
+
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.from_token_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.from_mint.to_account_info(),
+                from: ctx.accounts.user_from_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token_interface::burn(burn_ctx, amount)?;
+
+        let from_seeds = &[
+            b"vault_signer".as_ref(),
+            from_asset.mint.as_ref(),
+            &[from_vault.signer_version],
+            &[from_vault.signer_bump],
+        ];
+        let from_signer = &[&from_seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.from_vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.to_vault_usdc_account.to_account_info(),
+                authority: ctx.accounts.from_vault_signer.to_account_info(),
+            },
+            from_signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, moved, ctx.accounts.usdc_mint.decimals)?;
+
+        let to_seeds = &[
+            b"vault_signer".as_ref(),
+            to_asset.mint.as_ref(),
+            &[to_vault.signer_version],
+            &[to_vault.signer_bump],
+        ];
+        let to_signer = &[&to_seeds[..]];
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.to_token_program.to_account_info(),
+            token_interface::MintTo {
+                mint: ctx.accounts.to_mint.to_account_info(),
+                to: ctx.accounts.user_to_account.to_account_info(),
+                authority: ctx.accounts.to_vault_signer.to_account_info(),
+            },
+            to_signer,
+        );
+        token_interface::mint_to(mint_ctx, shares)?;
+
+        // Time-weighted balances follow the user's share ATAs
+        let user = ctx.accounts.user.key();
+        if ctx.accounts.user_from_account.key()
+            == get_associated_token_address_with_program_id(
+                &user,
+                &from_asset.mint,
+                &ctx.accounts.from_token_program.key(),
+            )
+        {
+            ctx.accounts.user_from_account.reload()?;
+            ctx.accounts
+                .from_position
+                .observe_balance(ctx.accounts.user_from_account.amount, now);
+        }
+        if ctx.accounts.user_to_account.key()
+            == get_associated_token_address_with_program_id(
+                &user,
+                &to_asset.mint,
+                &ctx.accounts.to_token_program.key(),
+            )
+        {
+            ctx.accounts.user_to_account.reload()?;
+            ctx.accounts
+                .to_position
+                .observe_balance(ctx.accounts.user_to_account.amount, now);
+        }
+
+        let redeem_fee_bps = from_vault.redeem_fee_bps;
+        from_vault.book_redemption(amount, usdc_amount, fee, redeem_fee_bps);
+        to_vault.total_usdc = new_total;
+        to_vault.total_assets = to_vault.total_assets.checked_add(shares).unwrap();
+        ctx.accounts.from_stats.record_redeem(usdc_amount);
+        ctx.accounts.to_stats.record_deposit(received, to_vault.total_backing());
+
+        emit_event!(ctx.accounts, RedeemEvent {
+            user: ctx.accounts.user.key(),
+            asset: from_asset.key(),
+            shares_in: amount,
+            usdc_out: moved,
+            price: from_asset.price,
+            memo: None,
+        });
+        emit_event!(ctx.accounts, DepositEvent {
+            user: ctx.accounts.user.key(),
+            asset: to_asset.key(),
+            usdc_in: received,
+            shares_out: shares,
+            price: to_asset.price,
+            memo: None,
+        });
+        emit_event!(ctx.accounts, AssetsSwapped {
+            user: ctx.accounts.user.key(),
+            from_vault: from_vault.key(),
+            to_vault: to_vault.key(),
+            shares_in: amount,
+            usdc_moved: moved,
+            shares_out: shares,
+        });
+
+        Ok(())
+    }
+
+    // Full exit: redeem the whole share balance and optionally close the
+    // emptied share account to reclaim its rent
+    pub fn redeem_all(ctx: Context<Redeem>, close_account: bool) -> Result<()> {
+        let amount = ctx.accounts.user_asset_account.amount;
+        require!(amount > 0, ErrorCode::NoSharesToRedeem);
+        let destination = ctx
+            .accounts
+            .user_usdc_account
+            .as_ref()
+            .ok_or(ErrorCode::MissingUnderlyingAccount)?
+            .to_account_info();
+        redeem_shares(ctx.accounts, destination, amount, None)?;
+
+        if close_account {
+            let close_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: ctx.accounts.user_asset_account.to_account_info(),
+                    destination: ctx.accounts.user.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            );
+            token_interface::close_account(close_ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn request_redeem<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RequestRedeem<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            vault.pause_flags & PAUSE_REDEMPTIONS == 0,
+            ErrorCode::RedemptionsPaused
+        );
+        require!(
+            !asset.delisted || Clock::get()?.unix_timestamp <= asset.settlement_deadline,
+            ErrorCode::SettlementWindowClosed
+        );
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let amount = vault.dust_adjusted(amount, ctx.accounts.user_asset_account.amount)?;
+
+        // Lock the shares with the vault until the epoch settles
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.user_asset_account.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.vault_asset_account.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            &[],
+        )?;
+
+        let request = &mut ctx.accounts.request;
+        request.vault = vault.key();
+        request.owner = ctx.accounts.user.key();
+        request.nonce = vault.redeem_request_nonce;
+        request.epoch = vault.redeem_epoch;
+        request.shares = amount;
+        request.receipt_mint = Pubkey::default();
+
+        vault.redeem_request_nonce = vault.redeem_request_nonce.checked_add(1).unwrap();
+        vault.queued_redeem_shares = vault.queued_redeem_shares.checked_add(amount).unwrap();
+        vault.queued_redeem_requests = vault.queued_redeem_requests.checked_add(1).unwrap();
+
+        Ok(())
+    }
+
+    pub fn cancel_redeem<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelRedeem<'info>>,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let request = &ctx.accounts.request;
+        require!(
+            request.epoch == vault.redeem_epoch,
+            ErrorCode::RedeemAlreadySettled
+        );
+        require!(
+            request.receipt_mint == Pubkey::default(),
+            ErrorCode::ReceiptOutstanding
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // Return the locked shares to the owner
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.vault_asset_account.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.owner_asset_account.to_account_info(),
+            &ctx.accounts.vault_signer.to_account_info(),
+            ctx.remaining_accounts,
+            request.shares,
+            signer,
+        )?;
+
+        vault.queued_redeem_shares = vault.queued_redeem_shares.checked_sub(request.shares).unwrap();
+        vault.queued_redeem_requests = vault.queued_redeem_requests.checked_sub(1).unwrap();
+
+        Ok(())
+    }
+
+    pub fn settle_redeem_epoch(ctx: Context<SettleRedeemEpoch>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            now >= vault.redeem_epoch_start.checked_add(vault.redeem_epoch_duration).unwrap(),
+            ErrorCode::RedeemEpochActive
+        );
+
+        let shares = vault.queued_redeem_shares;
+        require!(shares > 0, ErrorCode::NoPendingRedemptions);
+        vault.check_basket_fresh(now)?;
+
+        // Price every request in the epoch at the epoch-end price
+        let usdc_amount = vault.redemption_value(shares, asset.price, now);
+        let fee = bps_of(usdc_amount, vault.redeem_fee_bps);
+        let payout = usdc_amount.checked_sub(fee).unwrap();
+
+        // Burn the locked shares; the payout stays in the vault until claimed
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let burn_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                from: ctx.accounts.vault_asset_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::burn(burn_ctx, shares)?;
+
+        let epoch = &mut ctx.accounts.epoch;
+        epoch.vault = vault.key();
+        epoch.epoch = vault.redeem_epoch;
+        epoch.shares = shares;
+        epoch.payout = payout;
+        epoch.price = asset.price;
+
+        let redeem_fee_bps = vault.redeem_fee_bps;
+        vault.book_redemption(shares, usdc_amount, fee, redeem_fee_bps);
+        vault.claimable_usdc = vault.claimable_usdc.checked_add(payout).unwrap();
+        vault.queued_redeem_shares = 0;
+        vault.queued_redeem_requests = 0;
+        vault.redeem_epoch = vault.redeem_epoch.checked_add(1).unwrap();
+        vault.redeem_epoch_start = now;
+
+        pay_keeper_tip(
+            vault,
+            usdc_amount,
+            ctx.accounts.keeper_usdc_account.as_ref(),
+            &ctx.accounts.vault_usdc_account,
+            &ctx.accounts.usdc_mint,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.usdc_token_program,
+            signer,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn claim_withdrawal(ctx: Context<ClaimWithdrawal>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // Once a receipt exists only its holder can claim
+        require!(
+            ctx.accounts.request.receipt_mint == Pubkey::default(),
+            ErrorCode::ReceiptOutstanding
+        );
+
+        // Each request gets its share of the epoch payout
+        let epoch = &ctx.accounts.epoch;
+        let amount = (ctx.accounts.request.shares as u128)
+            .checked_mul(epoch.payout as u128)
+            .unwrap()
+            .checked_div(epoch.shares as u128)
+            .unwrap() as u64;
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.owner_usdc_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        vault.claimable_usdc = vault.claimable_usdc.checked_sub(amount).unwrap();
+
+        Ok(())
+    }
+
+    // Turn a queued redemption into a transferable Token-2022 NFT with
+    // on-mint metadata; whoever holds it claims the payout and burns it
+    pub fn mint_redeem_receipt(ctx: Context<MintRedeemReceipt>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.request.receipt_mint == Pubkey::default(),
+            ErrorCode::ReceiptAlreadyMinted
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let name = format!("{} redemption #{}", asset.ticker, ctx.accounts.request.nonce);
+        let symbol = asset.ticker.clone();
+        let uri = asset.uri.clone();
+
+        // The metadata extension reallocates the mint, so fund its rent first:
+        // TLV header, update authority, mint, three strings and an empty list
+        let receipt_mint = ctx.accounts.receipt_mint.to_account_info();
+        let metadata_len = 4 + 32 + 32 + 4 + name.len() + 4 + symbol.len() + 4 + uri.len() + 4;
+        let rent = Rent::get()?.minimum_balance(receipt_mint.data_len() + metadata_len);
+        let top_up = rent.saturating_sub(receipt_mint.lamports());
+        if top_up > 0 {
+            let rent_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: receipt_mint.clone(),
+                },
+            );
+            system_program::transfer(rent_ctx, top_up)?;
+        }
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let metadata_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_2022_program.to_account_info(),
+            TokenMetadataInitialize {
+                program_id: ctx.accounts.token_2022_program.to_account_info(),
+                metadata: receipt_mint.clone(),
+                update_authority: ctx.accounts.vault_signer.to_account_info(),
+                mint_authority: ctx.accounts.vault_signer.to_account_info(),
+                mint: receipt_mint.clone(),
+            },
+            signer,
+        );
+        token_interface::token_metadata_initialize(metadata_ctx, name, symbol, uri)?;
+
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_2022_program.to_account_info(),
+            token_interface::MintTo {
+                mint: receipt_mint.clone(),
+                to: ctx.accounts.receipt_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::mint_to(mint_ctx, 1)?;
+
+        ctx.accounts.request.receipt_mint = receipt_mint.key();
+
+        Ok(())
+    }
+
+    pub fn claim_redeem_receipt(ctx: Context<ClaimRedeemReceipt>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_2022_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                from: ctx.accounts.holder_receipt_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        );
+        token_interface::burn(burn_ctx, 1)?;
+
+        let epoch = &ctx.accounts.epoch;
+        let amount = (ctx.accounts.request.shares as u128)
+            .checked_mul(epoch.payout as u128)
+            .unwrap()
+            .checked_div(epoch.shares as u128)
+            .unwrap() as u64;
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.holder_usdc_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        vault.claimable_usdc = vault.claimable_usdc.checked_sub(amount).unwrap();
+
+        Ok(())
+    }
+
+    pub fn admin_borrow(ctx: Context<AdminBorrow>, amount: u64, reason: String) -> Result<()> {
+        let admin = &ctx.accounts.admin;
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+
+        // Verify admin authority
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            admin.withdraw_threshold <= 1,
+            ErrorCode::WithdrawalRequiresApproval
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            vault.signer_version == admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // Transfer USDC from vault to admin
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.admin_usdc_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        // Update vault state
+        let old_debt = vault.admin_debt;
+        vault.apply_admin_borrow(
+            amount,
+            ctx.accounts.asset.price,
+            Clock::get()?.unix_timestamp,
+        )?;
+        vault.log_admin_withdrawal(
+            &mut ctx.accounts.withdrawal_record,
+            amount,
+            ctx.accounts.admin_usdc_account.key(),
+            ctx.accounts.authority.key(),
+            reason,
+        )?;
+        emit_event!(ctx.accounts, AdminWithdrawal {
+            vault: vault.key(),
+            destination: ctx.accounts.admin_usdc_account.key(),
+            amount,
+            old_debt,
+            new_debt: vault.admin_debt,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_repay(ctx: Context<AdminRepay>, amount: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.accrue_admin_interest(Clock::get()?.unix_timestamp);
+        require!(amount <= vault.admin_debt, ErrorCode::RepayExceedsDebt);
+
+        // Transfer USDC from repayer to vault
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.repayer_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.vault_usdc_account.to_account_info(),
+                authority: ctx.accounts.repayer.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        // Only what reaches the vault after any transfer fee pays down debt
+        let received = amount
+            .checked_sub(transfer_fee(&ctx.accounts.usdc_mint, amount)?)
+            .unwrap();
+
+        // Update vault state
+        vault.admin_debt = vault.admin_debt.checked_sub(received).unwrap();
+        vault.total_usdc = vault.total_usdc.checked_add(received).unwrap();
+
+        Ok(())
+    }
+
+    pub fn set_borrow_rate(ctx: Context<UpdateVault>, borrow_rate_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        // Settle interest at the old rate before switching
+        let vault = &mut ctx.accounts.vault;
+        vault.accrue_admin_interest(Clock::get()?.unix_timestamp);
+        vault.admin_borrow_rate_bps = borrow_rate_bps;
+
+        Ok(())
+    }
+
+    pub fn set_min_collateral(ctx: Context<UpdateVault>, min_collateral_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts.vault.min_collateral_bps = min_collateral_bps;
+
+        Ok(())
+    }
+
+    pub fn set_withdraw_threshold(ctx: Context<UpdateAdmin>, threshold: u8) -> Result<()> {
+        let admin = &mut ctx.accounts.admin;
+        require!(
+            admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            threshold >= 1 && threshold as usize <= admin.authority_count(),
+            ErrorCode::InvalidThreshold
+        );
+
+        admin.withdraw_threshold = threshold;
+        Ok(())
+    }
+
+    pub fn propose_withdraw(ctx: Context<ProposeWithdraw>, amount: u64) -> Result<()> {
+        let proposer = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.admin.is_authority(proposer),
+            ErrorCode::Unauthorized
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.vault = vault.key();
+        proposal.nonce = vault.withdrawal_nonce;
+        proposal.proposer = proposer;
+        proposal.destination = ctx.accounts.admin_usdc_account.key();
+        proposal.amount = amount;
+        proposal.approvers = [Pubkey::default(); MAX_AUTHORITIES + 1];
+        proposal.approvers[0] = proposer;
+        proposal.eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.config.timelock_delay)
+            .unwrap();
+
+        vault.withdrawal_nonce = vault.withdrawal_nonce.checked_add(1).unwrap();
+
+        Ok(())
+    }
+
+    pub fn approve_withdraw(ctx: Context<ApproveWithdraw>) -> Result<()> {
+        let approver = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.admin.is_authority(approver),
+            ErrorCode::Unauthorized
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            !proposal.approvers.contains(&approver),
+            ErrorCode::AlreadyApproved
+        );
+
+        let slot = proposal
+            .approvers
+            .iter_mut()
+            .find(|key| **key == Pubkey::default())
+            .ok_or(ErrorCode::AlreadyApproved)?;
+        *slot = approver;
+
+        Ok(())
+    }
+
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>, reason: String) -> Result<()> {
+        let admin = &ctx.accounts.admin;
+        let vault = &mut ctx.accounts.vault;
+        let proposal = &ctx.accounts.proposal;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            admin.is_authority(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // Only approvals from current admins count towards the threshold
+        let approvals = proposal
+            .approvers
+            .iter()
+            .filter(|key| admin.is_authority(**key))
+            .count();
+        require!(
+            approvals >= admin.withdraw_threshold as usize,
+            ErrorCode::InsufficientApprovals
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.eta,
+            ErrorCode::TimelockActive
+        );
+
+        // Transfer USDC from vault to the proposal destination
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, proposal.amount, ctx.accounts.usdc_mint.decimals)?;
+
+        // Update vault state
+        let old_debt = vault.admin_debt;
+        vault.apply_admin_borrow(
+            proposal.amount,
+            ctx.accounts.asset.price,
+            Clock::get()?.unix_timestamp,
+        )?;
+        vault.log_admin_withdrawal(
+            &mut ctx.accounts.withdrawal_record,
+            proposal.amount,
+            proposal.destination,
+            ctx.accounts.authority.key(),
+            reason,
+        )?;
+        emit_event!(ctx.accounts, AdminWithdrawal {
+            vault: vault.key(),
+            destination: proposal.destination,
+            amount: proposal.amount,
+            old_debt,
+            new_debt: vault.admin_debt,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_timelock_delay(ctx: Context<UpdateConfig>, delay: i64) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        // Lengthening the delay is always safe; shortening it must be queued
+        let config = &mut ctx.accounts.config;
+        require!(delay >= config.timelock_delay, ErrorCode::TimelockRequired);
+
+        config.timelock_delay = delay;
+        Ok(())
+    }
+
+    pub fn queue_action(
+        ctx: Context<QueueAction>,
+        vault: Pubkey,
+        action: TimelockedAction,
+    ) -> Result<()> {
+        let admin = &ctx.accounts.admin;
+        let signer = ctx.accounts.authority.key();
+        let authorized = match action {
+            // Asset-scoped changes belong to the asset's own authority
+            TimelockedAction::SetDepositLimit { .. }
+            | TimelockedAction::SetPerUserCap { .. }
+            | TimelockedAction::SetMinDeposit { .. }
+            | TimelockedAction::SetDustThreshold { .. }
+            | TimelockedAction::SetRedeemCooldown { .. }
+            | TimelockedAction::SetRedeemEpochDuration { .. }
+            | TimelockedAction::SetRedeemRateLimit { .. }
+            | TimelockedAction::SetDepositInflowCap { .. }
+            | TimelockedAction::SetFees { .. }
+            | TimelockedAction::SetBufferTarget { .. }
+            | TimelockedAction::Delist { .. } => ctx
+                .accounts
+                .asset
+                .as_ref()
+                .is_some_and(|asset| asset.vault == vault && asset.authority == signer),
+            TimelockedAction::AdminWithdraw { .. }
+            | TimelockedAction::SetWithdrawCap { .. }
+            | TimelockedAction::SetBorrowRate { .. }
+            | TimelockedAction::SetMinCollateral { .. }
+            | TimelockedAction::SetTimelockDelay { .. }
+            | TimelockedAction::SetCreationFee { .. }
+            | TimelockedAction::SetMinInitialDeposit { .. }
+            | TimelockedAction::SetListingBond { .. }
+            | TimelockedAction::SetGaugeEmissionRate { .. } => {
+                admin.has_role(signer, Role::SuperAdmin, ctx.accounts.role_grant.as_deref())
+            }
+            TimelockedAction::SetReferralFee { .. }
+            | TimelockedAction::SetPerformanceFee { .. }
+            | TimelockedAction::SetProfitUnlockPeriod { .. }
+            | TimelockedAction::SetRebalanceCap { .. }
+            | TimelockedAction::SetKeeperTip { .. }
+            | TimelockedAction::SetDonationPolicy { .. }
+            | TimelockedAction::SetStrategyLimits { .. } => {
+                admin.has_role(signer, Role::VaultManager, ctx.accounts.role_grant.as_deref())
+            }
+        };
+        require!(authorized, ErrorCode::Unauthorized);
+
+        match action {
+            TimelockedAction::SetFees { deposit_fee_bps, redeem_fee_bps, .. } => require!(
+                deposit_fee_bps <= MAX_FEE_BPS && redeem_fee_bps <= MAX_FEE_BPS,
+                ErrorCode::FeeTooHigh
+            ),
+            TimelockedAction::SetReferralFee { referral_fee_bps } => require!(
+                referral_fee_bps as u64 <= BPS_DENOMINATOR,
+                ErrorCode::FeeTooHigh
+            ),
+            TimelockedAction::SetRedeemCooldown { redeem_cooldown_slots } => require!(
+                redeem_cooldown_slots <= MAX_REDEEM_COOLDOWN_SLOTS,
+                ErrorCode::InvalidRedeemCooldown
+            ),
+            TimelockedAction::SetDepositInflowCap { inflow_cap, inflow_window_slots } => require!(
+                inflow_cap == 0 || inflow_window_slots > 0,
+                ErrorCode::InvalidInflowWindow
+            ),
+            TimelockedAction::SetRedeemEpochDuration { redeem_epoch_duration } => require!(
+                (0..=MAX_REDEEM_EPOCH_DURATION).contains(&redeem_epoch_duration),
+                ErrorCode::InvalidRedeemEpochDuration
+            ),
+            TimelockedAction::Delist { settlement_price, window } => require!(
+                settlement_price > 0 && window > 0,
+                ErrorCode::InvalidSettlement
+            ),
+            TimelockedAction::SetBufferTarget { buffer_target_bps } => require!(
+                buffer_target_bps as u64 <= BPS_DENOMINATOR,
+                ErrorCode::InvalidBufferTarget
+            ),
+            TimelockedAction::SetPerformanceFee { performance_fee_bps } => require!(
+                performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+                ErrorCode::FeeTooHigh
+            ),
+            TimelockedAction::SetProfitUnlockPeriod { period } => require!(
+                (0..=MAX_PROFIT_UNLOCK_PERIOD).contains(&period),
+                ErrorCode::InvalidProfitUnlockPeriod
+            ),
+            TimelockedAction::SetRebalanceCap { rebalance_cap_bps } => require!(
+                rebalance_cap_bps as u64 <= BPS_DENOMINATOR,
+                ErrorCode::InvalidRebalanceCap
+            ),
+            TimelockedAction::SetKeeperTip { keeper_tip_bps, .. } => {
+                require!(keeper_tip_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh)
+            }
+            TimelockedAction::SetStrategyLimits { debt_ratio_bps, .. } => require!(
+                debt_ratio_bps as u64 <= BPS_DENOMINATOR,
+                ErrorCode::InvalidDebtRatio
+            ),
+            TimelockedAction::SetDonationPolicy { .. }
+            | TimelockedAction::SetCreationFee { .. }
+            | TimelockedAction::SetMinInitialDeposit { .. }
+            | TimelockedAction::SetListingBond { .. }
+            | TimelockedAction::SetGaugeEmissionRate { .. } => {}
+            TimelockedAction::SetWithdrawCap { withdraw_cap_bps } => require!(
+                withdraw_cap_bps as u64 <= BPS_DENOMINATOR,
+                ErrorCode::InvalidWithdrawCap
+            ),
+            TimelockedAction::AdminWithdraw { .. } => require!(
+                admin.withdraw_threshold <= 1,
+                ErrorCode::WithdrawalRequiresApproval
+            ),
+            TimelockedAction::SetBorrowRate { .. }
+            | TimelockedAction::SetMinCollateral { .. }
+            | TimelockedAction::SetDepositLimit { .. }
+            | TimelockedAction::SetPerUserCap { .. }
+            | TimelockedAction::SetMinDeposit { .. }
+            | TimelockedAction::SetDustThreshold { .. }
+            | TimelockedAction::SetRedeemRateLimit { .. } => {}
+            TimelockedAction::SetTimelockDelay { delay } => {
+                require!(delay >= 0, ErrorCode::InvalidTimelockDelay)
+            }
+        }
+
+        let config = &mut ctx.accounts.config;
+        let pending = &mut ctx.accounts.pending_action;
+        pending.nonce = config.action_nonce;
+        pending.proposer = signer;
+        pending.vault = vault;
+        pending.action = action;
+        pending.eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(config.timelock_delay)
+            .unwrap();
+
+        config.action_nonce = config.action_nonce.checked_add(1).unwrap();
+
+        Ok(())
+    }
+
+    pub fn cancel_action(ctx: Context<CancelAction>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn execute_action(ctx: Context<ExecuteAction>) -> Result<()> {
+        let pending = &ctx.accounts.pending_action;
+        require!(
+            Clock::get()?.unix_timestamp >= pending.eta,
+            ErrorCode::TimelockActive
+        );
+
+        // Protocol-wide actions only touch the config
+        let config = &mut ctx.accounts.config;
+        match pending.action {
+            TimelockedAction::SetTimelockDelay { delay } => {
+                config.timelock_delay = delay;
+                return Ok(());
+            }
+            TimelockedAction::SetCreationFee { creation_fee, treasury } => {
+                emit_event!(ctx.accounts, CreationFeeUpdated {
+                    old_fee: config.creation_fee,
+                    new_fee: creation_fee,
+                    old_treasury: config.treasury,
+                    new_treasury: treasury,
+                });
+                config.creation_fee = creation_fee;
+                config.treasury = treasury;
+                return Ok(());
+            }
+            TimelockedAction::SetMinInitialDeposit { min_initial_deposit } => {
+                config.min_initial_deposit = min_initial_deposit;
+                return Ok(());
+            }
+            TimelockedAction::SetListingBond { listing_bond } => {
+                config.listing_bond = listing_bond;
+                return Ok(());
+            }
+            TimelockedAction::SetGaugeEmissionRate { gauge_emission_rate } => {
+                config.gauge_emission_rate = gauge_emission_rate;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // Every other action targets the vault recorded at queue time
+        let (Some(asset), Some(vault)) = (ctx.accounts.asset.as_mut(), ctx.accounts.vault.as_mut())
+        else {
+            return err!(ErrorCode::InvalidActionTarget);
+        };
+        require!(
+            vault.key() == pending.vault && asset.vault == vault.key(),
+            ErrorCode::InvalidActionTarget
+        );
+
+        match pending.action {
+            TimelockedAction::SetFees {
+                deposit_fee_bps,
+                redeem_fee_bps,
+                fee_recipient,
+                fee_mode,
+            } => {
+                emit_event!(ctx.accounts, FeesUpdated {
+                    vault: vault.key(),
+                    old_deposit_fee_bps: vault.deposit_fee_bps,
+                    new_deposit_fee_bps: deposit_fee_bps,
+                    old_redeem_fee_bps: vault.redeem_fee_bps,
+                    new_redeem_fee_bps: redeem_fee_bps,
+                    old_fee_recipient: vault.fee_recipient,
+                    new_fee_recipient: fee_recipient,
+                    old_fee_mode: vault.fee_mode,
+                    new_fee_mode: fee_mode,
+                });
+                vault.deposit_fee_bps = deposit_fee_bps;
+                vault.redeem_fee_bps = redeem_fee_bps;
+                vault.fee_recipient = fee_recipient;
+                vault.fee_mode = fee_mode;
+            }
+            TimelockedAction::SetReferralFee { referral_fee_bps } => {
+                emit_event!(ctx.accounts, VaultFeeUpdated {
+                    vault: vault.key(),
+                    kind: VaultFee::Referral,
+                    old_bps: vault.referral_fee_bps,
+                    new_bps: referral_fee_bps,
+                });
+                vault.referral_fee_bps = referral_fee_bps;
+            }
+            TimelockedAction::SetDepositLimit { deposit_limit } => {
+                emit_event!(ctx.accounts, DepositLimitUpdated {
+                    vault: vault.key(),
+                    old_limit: vault.deposit_limit,
+                    new_limit: deposit_limit,
+                });
+                vault.deposit_limit = deposit_limit;
+            }
+            TimelockedAction::SetPerUserCap { per_user_cap } => {
+                emit_event!(ctx.accounts, VaultLimitUpdated {
+                    vault: vault.key(),
+                    kind: VaultLimit::PerUserCap,
+                    old_value: vault.per_user_cap,
+                    new_value: per_user_cap,
+                });
+                vault.per_user_cap = per_user_cap;
+            }
+            TimelockedAction::SetMinDeposit { min_deposit } => {
+                emit_event!(ctx.accounts, VaultLimitUpdated {
+                    vault: vault.key(),
+                    kind: VaultLimit::MinDeposit,
+                    old_value: vault.min_deposit,
+                    new_value: min_deposit,
+                });
+                vault.min_deposit = min_deposit;
+            }
+            TimelockedAction::SetDustThreshold { dust_threshold, sweep_dust } => {
+                vault.dust_threshold = dust_threshold;
+                vault.sweep_dust = sweep_dust;
+            }
+            TimelockedAction::SetRedeemCooldown { redeem_cooldown_slots } => {
+                vault.redeem_cooldown_slots = redeem_cooldown_slots;
+            }
+            TimelockedAction::SetRedeemEpochDuration { redeem_epoch_duration } => {
+                vault.redeem_epoch_duration = redeem_epoch_duration;
+            }
+            TimelockedAction::SetDepositInflowCap { inflow_cap, inflow_window_slots } => {
+                emit_event!(ctx.accounts, VaultLimitUpdated {
+                    vault: vault.key(),
+                    kind: VaultLimit::InflowCap,
+                    old_value: vault.inflow_cap,
+                    new_value: inflow_cap,
+                });
+                vault.inflow_cap = inflow_cap;
+                vault.inflow_window_slots = inflow_window_slots;
+            }
+            TimelockedAction::SetRedeemRateLimit { redeem_rate_limit } => {
+                emit_event!(ctx.accounts, VaultLimitUpdated {
+                    vault: vault.key(),
+                    kind: VaultLimit::RedeemRateLimit,
+                    old_value: vault.redeem_rate_limit,
+                    new_value: redeem_rate_limit,
+                });
+                vault.set_redeem_rate_limit(redeem_rate_limit, Clock::get()?.unix_timestamp);
+            }
+            TimelockedAction::Delist { settlement_price, window } => {
+                asset.delist(settlement_price, window, Clock::get()?.unix_timestamp)?;
+            }
+            TimelockedAction::SetWithdrawCap { withdraw_cap_bps } => {
+                emit_event!(ctx.accounts, VaultLimitUpdated {
+                    vault: vault.key(),
+                    kind: VaultLimit::WithdrawCap,
+                    old_value: vault.withdraw_cap_bps as u64,
+                    new_value: withdraw_cap_bps as u64,
+                });
+                vault.withdraw_cap_bps = withdraw_cap_bps;
+            }
+            TimelockedAction::SetBufferTarget { buffer_target_bps } => {
+                vault.buffer_target_bps = buffer_target_bps;
+            }
+            TimelockedAction::SetPerformanceFee { performance_fee_bps } => {
+                emit_event!(ctx.accounts, VaultFeeUpdated {
+                    vault: vault.key(),
+                    kind: VaultFee::Performance,
+                    old_bps: vault.performance_fee_bps,
+                    new_bps: performance_fee_bps,
+                });
+                vault.performance_fee_bps = performance_fee_bps;
+            }
+            TimelockedAction::SetProfitUnlockPeriod { period } => {
+                vault.set_profit_unlock_period(period, Clock::get()?.unix_timestamp);
+            }
+            TimelockedAction::SetRebalanceCap { rebalance_cap_bps } => {
+                vault.rebalance_cap_bps = rebalance_cap_bps;
+            }
+            TimelockedAction::SetKeeperTip {
+                keeper_tip,
+                keeper_tip_bps,
+            } => {
+                vault.keeper_tip = keeper_tip;
+                vault.keeper_tip_bps = keeper_tip_bps;
+            }
+            TimelockedAction::SetDonationPolicy { sweep_donations } => {
+                vault.sweep_donations = sweep_donations;
+            }
+            TimelockedAction::SetBorrowRate { borrow_rate_bps } => {
+                vault.accrue_admin_interest(Clock::get()?.unix_timestamp);
+                vault.admin_borrow_rate_bps = borrow_rate_bps;
+            }
+            TimelockedAction::SetMinCollateral { min_collateral_bps } => {
+                vault.min_collateral_bps = min_collateral_bps;
+            }
+            TimelockedAction::SetStrategyLimits {
+                adapter_program,
+                max_debt,
+                debt_ratio_bps,
+            } => {
+                let strategy = ctx
+                    .accounts
+                    .strategy
+                    .as_mut()
+                    .ok_or(ErrorCode::InvalidActionTarget)?;
+                require!(
+                    strategy.vault == vault.key() && strategy.adapter_program == adapter_program,
+                    ErrorCode::InvalidActionTarget
+                );
+                strategy.set_limits(vault, max_debt, debt_ratio_bps)?;
+            }
+            // Withdrawals move funds and go through execute_withdraw_action
+            TimelockedAction::AdminWithdraw { .. }
+            | TimelockedAction::SetTimelockDelay { .. }
+            | TimelockedAction::SetCreationFee { .. }
+            | TimelockedAction::SetMinInitialDeposit { .. }
+            | TimelockedAction::SetListingBond { .. }
+            | TimelockedAction::SetGaugeEmissionRate { .. } => {
+                return err!(ErrorCode::InvalidActionTarget);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn execute_withdraw_action(
+        ctx: Context<ExecuteWithdrawAction>,
+        reason: String,
+    ) -> Result<()> {
+        let admin = &ctx.accounts.admin;
+        let vault = &mut ctx.accounts.vault;
+        let pending = &ctx.accounts.pending_action;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            Clock::get()?.unix_timestamp >= pending.eta,
+            ErrorCode::TimelockActive
+        );
+        require!(
+            admin.withdraw_threshold <= 1,
+            ErrorCode::WithdrawalRequiresApproval
+        );
+        require!(
+            vault.signer_version == admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let TimelockedAction::AdminWithdraw { amount, destination } = pending.action else {
+            return err!(ErrorCode::InvalidActionTarget);
+        };
+        require!(
+            pending.vault == vault.key() && destination == ctx.accounts.destination.key(),
+            ErrorCode::InvalidActionTarget
+        );
+
+        // Transfer USDC from vault to the queued destination
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        // Update vault state
+        let old_debt = vault.admin_debt;
+        vault.apply_admin_borrow(
+            amount,
+            ctx.accounts.asset.price,
+            Clock::get()?.unix_timestamp,
+        )?;
+        vault.log_admin_withdrawal(
+            &mut ctx.accounts.withdrawal_record,
+            amount,
+            destination,
+            pending.proposer,
+            reason,
+        )?;
+        emit_event!(ctx.accounts, AdminWithdrawal {
+            vault: vault.key(),
+            destination,
+            amount,
+            old_debt,
+            new_debt: vault.admin_debt,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_fees(
+        ctx: Context<UpdateVault>,
+        deposit_fee_bps: u16,
+        redeem_fee_bps: u16,
+        fee_recipient: Pubkey,
+        fee_mode: FeeMode,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            deposit_fee_bps <= MAX_FEE_BPS && redeem_fee_bps <= MAX_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        emit_event!(ctx.accounts, FeesUpdated {
+            vault: vault.key(),
+            old_deposit_fee_bps: vault.deposit_fee_bps,
+            new_deposit_fee_bps: deposit_fee_bps,
+            old_redeem_fee_bps: vault.redeem_fee_bps,
+            new_redeem_fee_bps: redeem_fee_bps,
+            old_fee_recipient: vault.fee_recipient,
+            new_fee_recipient: fee_recipient,
+            old_fee_mode: vault.fee_mode,
+            new_fee_mode: fee_mode,
+        });
+        vault.deposit_fee_bps = deposit_fee_bps;
+        vault.redeem_fee_bps = redeem_fee_bps;
+        vault.fee_recipient = fee_recipient;
+        vault.fee_mode = fee_mode;
+
+        Ok(())
+    }
+
+    pub fn set_referral_fee(ctx: Context<UpdateVault>, referral_fee_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            referral_fee_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::FeeTooHigh
+        );
+
+        emit_event!(ctx.accounts, VaultFeeUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultFee::Referral,
+            old_bps: ctx.accounts.vault.referral_fee_bps,
+            new_bps: referral_fee_bps,
+        });
+        ctx.accounts.vault.referral_fee_bps = referral_fee_bps;
+
+        Ok(())
+    }
+
+    pub fn set_buffer_target(ctx: Context<UpdateVault>, buffer_target_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            buffer_target_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidBufferTarget
+        );
+
+        ctx.accounts.vault.buffer_target_bps = buffer_target_bps;
+
+        Ok(())
+    }
+
+    pub fn set_performance_fee(ctx: Context<UpdateVault>, performance_fee_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+
+        emit_event!(ctx.accounts, VaultFeeUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultFee::Performance,
+            old_bps: ctx.accounts.vault.performance_fee_bps,
+            new_bps: performance_fee_bps,
+        });
+        ctx.accounts.vault.performance_fee_bps = performance_fee_bps;
+
+        Ok(())
+    }
+
+    // Automation threads sign cranks with their own PDA; the registered thread
+    // is treated as a keeper for this vault
+    pub fn register_automation_thread(ctx: Context<UpdateVault>, thread: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(thread != Pubkey::default(), ErrorCode::InvalidAutomationThread);
+
+        let vault = &mut ctx.accounts.vault;
+        vault.automation_thread = thread;
+        emit_event!(ctx.accounts, AutomationThreadUpdated {
+            vault: vault.key(),
+            thread,
+        });
+
+        Ok(())
+    }
+
+    pub fn unregister_automation_thread(ctx: Context<UpdateVault>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.automation_thread = Pubkey::default();
+        emit_event!(ctx.accounts, AutomationThreadUpdated {
+            vault: vault.key(),
+            thread: Pubkey::default(),
+        });
+
+        Ok(())
+    }
+
+    pub fn set_keeper_tip(
+        ctx: Context<UpdateVault>,
+        keeper_tip: u64,
+        keeper_tip_bps: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(keeper_tip_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh);
+
+        let vault = &mut ctx.accounts.vault;
+        vault.keeper_tip = keeper_tip;
+        vault.keeper_tip_bps = keeper_tip_bps;
+
+        Ok(())
+    }
+
+    pub fn set_rebalance_cap(ctx: Context<UpdateVault>, rebalance_cap_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            rebalance_cap_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidRebalanceCap
+        );
+
+        ctx.accounts.vault.rebalance_cap_bps = rebalance_cap_bps;
+
+        Ok(())
+    }
+
+    pub fn set_profit_unlock_period(ctx: Context<UpdateVault>, period: i64) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            (0..=MAX_PROFIT_UNLOCK_PERIOD).contains(&period),
+            ErrorCode::InvalidProfitUnlockPeriod
+        );
+
+        ctx.accounts
+            .vault
+            .set_profit_unlock_period(period, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    // Permissionless crank: withdraw from strategies until the idle buffer is
+    // back at its target. Remaining accounts come in one group per strategy:
+    // the strategy, its adapter program, then `account_counts[i]` adapter
+    // accounts. Only debt is recalled; marked gains are left for harvest
+    pub fn rebalance_buffer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RebalanceBuffer<'info>>,
+        account_counts: Vec<u8>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            account_counts.iter().map(|count| *count as usize + 2).sum::<usize>()
+                == ctx.remaining_accounts.len(),
+            ErrorCode::InvalidRebalance
+        );
+
+        let vault_key = vault.key();
+        let target = vault.buffer_target();
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+
+        let mut withdrawn: u64 = 0;
+        let mut remaining = ctx.remaining_accounts;
+        for count in account_counts {
+            let (group, rest) = remaining.split_at(count as usize + 2);
+            remaining = rest;
+
+            let shortfall = target.saturating_sub(ctx.accounts.vault.total_usdc);
+            if shortfall == 0 {
+                break;
+            }
+
+            let mut strategy = Account::<Strategy>::try_from(&group[0])?;
+            require!(
+                strategy.vault == vault_key && group[1].key() == strategy.adapter_program,
+                ErrorCode::InvalidRebalance
+            );
+            strategy.check_accounts(ctx.accounts.vault_signer.key(), &group[2..])?;
+            let amount = shortfall.min(strategy.current_debt);
+            if amount == 0 {
+                continue;
+            }
+
+            let before = ctx.accounts.vault_usdc_account.amount;
+            invoke_adapter(
+                &group[1],
+                "withdraw",
+                amount,
+                &ctx.accounts.vault_signer,
+                &ctx.accounts.vault_usdc_account.to_account_info(),
+                &group[2..],
+                &[&seeds[..]],
+            )?;
+            ctx.accounts.vault_usdc_account.reload()?;
+            let received = ctx.accounts.vault_usdc_account.amount.checked_sub(before).unwrap();
+
+            // Booked like withdraw_from_strategy: debt first, then marked gains
+            let repaid = received.min(strategy.current_debt);
+            let realized = (received - repaid).min(strategy.unrealized_gain);
+            strategy.current_debt = strategy.current_debt.checked_sub(repaid).unwrap();
+            strategy.unrealized_gain = strategy.unrealized_gain.checked_sub(realized).unwrap();
+            strategy.total_value = strategy.total_value.saturating_sub(received);
+            strategy.exit(&crate::ID)?;
+
+            let vault = &mut ctx.accounts.vault;
+            vault.strategy_debt = vault.strategy_debt.checked_sub(repaid).unwrap();
+            vault.strategy_gain = vault.strategy_gain.checked_sub(realized).unwrap();
+            vault.total_usdc = vault.total_usdc.checked_add(received).unwrap();
+            withdrawn = withdrawn.checked_add(received).unwrap();
+        }
+
+        let vault = &ctx.accounts.vault;
+        emit_event!(ctx.accounts, BufferRebalanced {
+            vault: vault_key,
+            target,
+            liquid: vault.total_usdc,
+            shortfall: target.saturating_sub(vault.total_usdc),
+            withdrawn,
+        });
+        Ok(())
+    }
+
+    pub fn whitelist_adapter(ctx: Context<WhitelistAdapter>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.adapter_program.executable,
+            ErrorCode::AdapterNotWhitelisted
+        );
+
+        ctx.accounts.adapter_entry.program = ctx.accounts.adapter_program.key();
+        Ok(())
+    }
+
+    pub fn remove_adapter(ctx: Context<RemoveAdapter>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    // The remaining accounts are [token_account, strategy_account] pairs for
+    // each vault-signer token account the adapter keeps positions in (cTokens,
+    // position NFTs, ...). Adapter calls may touch no other vault-signer
+    // accounts, and the markers keep these out of sweep_tokens
+    pub fn add_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddStrategy<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.remaining_accounts.len().is_multiple_of(2)
+                && ctx.remaining_accounts.len() <= MAX_STRATEGY_ACCOUNTS * 2,
+            ErrorCode::InvalidStrategyAccount
+        );
+
+        let vault_key = ctx.accounts.vault.key();
+        let strategy_key = ctx.accounts.strategy.key();
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let mut token_accounts = [Pubkey::default(); MAX_STRATEGY_ACCOUNTS];
+        for (slot, pair) in token_accounts.iter_mut().zip(ctx.remaining_accounts.chunks(2)) {
+            let (token_info, marker) = (&pair[0], &pair[1]);
+            let token = InterfaceAccount::<token_interface::TokenAccount>::try_from(token_info)?;
+            let (marker_key, marker_bump) = Pubkey::find_program_address(
+                &[b"strategy_account", vault_key.as_ref(), token_info.key.as_ref()],
+                &crate::ID,
+            );
+            require!(
+                token.owner == ctx.accounts.vault_signer.key() && marker.key() == marker_key,
+                ErrorCode::InvalidStrategyAccount
+            );
+
+            // An account already claimed by another strategy fails here
+            let seeds = [
+                b"strategy_account".as_ref(),
+                vault_key.as_ref(),
+                token_info.key.as_ref(),
+                &[marker_bump],
+            ];
+            let signer = &[&seeds[..]];
+            let create_ctx = CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: authority.clone(),
+                    to: marker.clone(),
+                },
+                signer,
+            );
+            let space = 8 + StrategyAccount::LEN;
+            system_program::create_account(
+                create_ctx,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                &crate::ID,
+            )?;
+            let mut entry = Account::<StrategyAccount>::try_from_unchecked(marker)?;
+            entry.strategy = strategy_key;
+            entry.exit(&crate::ID)?;
+            *slot = token_info.key();
+        }
+
+        let strategy = &mut ctx.accounts.strategy;
+        strategy.vault = ctx.accounts.vault.key();
+        strategy.adapter_program = ctx.accounts.adapter_entry.program;
+        strategy.current_debt = 0;
+        strategy.total_value = 0;
+        strategy.unrealized_gain = 0;
+        strategy.max_debt = 0;
+        strategy.debt_ratio_bps = 0;
+        strategy.token_accounts = token_accounts;
+        Ok(())
+    }
+
+    // Caps what a strategy may hold, both absolutely and as a share of the
+    // vault's backing, so a faulty adapter can only lose a bounded amount
+    pub fn set_strategy_limits(
+        ctx: Context<UpdateStrategy>,
+        max_debt: u64,
+        debt_ratio_bps: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts
+            .strategy
+            .set_limits(&mut ctx.accounts.vault, max_debt, debt_ratio_bps)
+    }
+
+    pub fn deploy_to_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyFunds<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(!vault.shutdown, ErrorCode::VaultShutdown);
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // Removed adapters can still be withdrawn from, but not deployed to
+        require!(
+            ctx.accounts.adapter_entry.is_some(),
+            ErrorCode::AdapterNotWhitelisted
+        );
+
+        // Only USDC above the liquidity buffer can be deployed
+        let remaining = vault.total_usdc.checked_sub(amount).unwrap();
+        require!(
+            remaining >= vault.buffer_target(),
+            ErrorCode::BufferTargetBreached
+        );
+
+        // Debt after the deploy must stay within the strategy's ceiling and
+        // its allocation of the vault's backing
+        let strategy = &ctx.accounts.strategy;
+        let debt = strategy.current_debt.checked_add(amount).unwrap();
+        require!(
+            debt <= strategy.max_debt
+                && debt <= bps_of(vault.total_backing(), strategy.debt_ratio_bps),
+            ErrorCode::StrategyDebtLimitExceeded
+        );
+        strategy.check_accounts(ctx.accounts.vault_signer.key(), ctx.remaining_accounts)?;
+
+        // The adapter pulls the USDC under the vault signer's authority
+        let before = ctx.accounts.vault_usdc_account.amount;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        invoke_adapter(
+            &ctx.accounts.adapter_program,
+            "deposit",
+            amount,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+
+        // Adapters that size positions in their own units (e.g. liquidity) may
+        // leave rounding dust behind, so only what was actually spent is booked
+        ctx.accounts.vault_usdc_account.reload()?;
+        let spent = before.checked_sub(ctx.accounts.vault_usdc_account.amount).unwrap();
+        require!(spent <= amount, ErrorCode::AdapterAmountMismatch);
+
+        let vault = &mut ctx.accounts.vault;
+        vault.total_usdc = vault.total_usdc.checked_sub(spent).unwrap();
+        vault.strategy_debt = vault.strategy_debt.checked_add(spent).unwrap();
+        let strategy = &mut ctx.accounts.strategy;
+        strategy.current_debt = strategy.current_debt.checked_add(spent).unwrap();
+        strategy.total_value = strategy.total_value.checked_add(spent).unwrap();
+
+        Ok(())
+    }
+
+    // `u64::MAX` asks the adapter for a full exit; pausers may trigger one as
+    // an emergency withdrawal
+    pub fn withdraw_from_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyFunds<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let authority = ctx.accounts.authority.key();
+        let role_grant = ctx.accounts.role_grant.as_deref();
+        require!(
+            ctx.accounts.admin.has_role(authority, Role::VaultManager, role_grant)
+                || (amount == u64::MAX
+                    && ctx.accounts.admin.has_role(authority, Role::Pauser, role_grant)),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts
+            .strategy
+            .check_accounts(ctx.accounts.vault_signer.key(), ctx.remaining_accounts)?;
+
+        // The adapter returns USDC to the vault token account. Positions must
+        // be unwound before a rotated vault can migrate, so this also runs
+        // under an outdated signer
+        let before = ctx.accounts.vault_usdc_account.amount;
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        invoke_adapter(
+            &ctx.accounts.adapter_program,
+            "withdraw",
+            amount,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+
+        // Adapters convert into their own units (e.g. cTokens) and round
+        // down, so the amount actually returned is what gets booked
+        ctx.accounts.vault_usdc_account.reload()?;
+        let received = ctx.accounts.vault_usdc_account.amount.checked_sub(before).unwrap();
+
+        // Anything returned beyond the strategy's debt realizes marked gains
+        let strategy = &mut ctx.accounts.strategy;
+        let repaid = received.min(strategy.current_debt);
+        let realized = (received - repaid).min(strategy.unrealized_gain);
+        strategy.current_debt = strategy.current_debt.checked_sub(repaid).unwrap();
+        strategy.unrealized_gain = strategy.unrealized_gain.checked_sub(realized).unwrap();
+        strategy.total_value = strategy.total_value.saturating_sub(received);
+        let vault = &mut ctx.accounts.vault;
+        vault.strategy_debt = vault.strategy_debt.checked_sub(repaid).unwrap();
+        vault.strategy_gain = vault.strategy_gain.checked_sub(realized).unwrap();
+        vault.total_usdc = vault.total_usdc.checked_add(received).unwrap();
+
+        Ok(())
+    }
+
+    // The adapter values whatever accounts it's handed, so marks are limited
+    // to keepers; an open mark at 0 would wipe the strategy's gain
+    pub fn mark_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyFunds<'info>>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        ctx.accounts
+            .strategy
+            .check_accounts(ctx.accounts.vault_signer.key(), ctx.remaining_accounts)?;
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let value = adapter_total_value(
+            &ctx.accounts.adapter_program,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+        ctx.accounts
+            .strategy
+            .mark(&mut ctx.accounts.vault, value, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    // Keeper crank: marks the strategy, pulls its gain back into the vault
+    // and charges the performance fee on what was realized. Adapters value at
+    // spot, which a caller can move within its own transaction, so it isn't
+    // open to anyone
+    pub fn harvest<'info>(ctx: Context<'_, '_, 'info, 'info, StrategyFunds<'info>>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        ctx.accounts
+            .strategy
+            .check_accounts(ctx.accounts.vault_signer.key(), ctx.remaining_accounts)?;
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let value = adapter_total_value(
+            &ctx.accounts.adapter_program,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+        ctx.accounts
+            .strategy
+            .mark(&mut ctx.accounts.vault, value, Clock::get()?.unix_timestamp);
+        // Nothing to realize is a no-op so scheduled runs don't fail
+        let gain = ctx.accounts.strategy.unrealized_gain;
+        if gain == 0 {
+            return Ok(());
+        }
+
+        let before = ctx.accounts.vault_usdc_account.amount;
+        invoke_adapter(
+            &ctx.accounts.adapter_program,
+            "withdraw",
+            gain,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        let received = ctx.accounts.vault_usdc_account.amount.checked_sub(before).unwrap();
+
+        // Proceeds settle the marked gain first; any surplus repays debt
+        let strategy = &mut ctx.accounts.strategy;
+        let realized = received.min(strategy.unrealized_gain);
+        let repaid = (received - realized).min(strategy.current_debt);
+        strategy.unrealized_gain = strategy.unrealized_gain.checked_sub(realized).unwrap();
+        strategy.current_debt = strategy.current_debt.checked_sub(repaid).unwrap();
+        strategy.total_value = strategy.total_value.saturating_sub(received);
+
+        let vault = &mut ctx.accounts.vault;
+        vault.strategy_gain = vault.strategy_gain.checked_sub(realized).unwrap();
+        vault.strategy_debt = vault.strategy_debt.checked_sub(repaid).unwrap();
+
+        vault.total_usdc = vault.total_usdc.checked_add(received - realized).unwrap();
+        let fee = vault.book_profit(realized, ctx.accounts.asset.price);
+        vault.release_locked_profit(fee, Clock::get()?.unix_timestamp);
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        pay_keeper_tip(
+            vault,
+            realized,
+            ctx.accounts.keeper_usdc_account.as_ref(),
+            &ctx.accounts.vault_usdc_account,
+            &ctx.accounts.usdc_mint,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.token_program,
+            &[&seeds[..]],
+        )?;
+
+        emit_event!(ctx.accounts, Harvest {
+            vault: vault.key(),
+            strategy: strategy.key(),
+            gross_gain: realized,
+            fee,
+            net_gain: realized.checked_sub(fee).unwrap(),
+        });
+
+        Ok(())
+    }
+
+    // Write a strategy's debt down by `loss`, up to the whole debt. Only vault
+    // managers may: adapters value whatever accounts they're handed, so an
+    // adapter-backed check would let anyone write off deployed funds. The
+    // backing drops for every holder at once, and redemptions pay out
+    // pro-rata while the vault is in recovery
+    pub fn report_loss<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyFunds<'info>>,
+        loss: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            loss > 0 && loss <= ctx.accounts.strategy.current_debt,
+            ErrorCode::InvalidLoss
+        );
+
+        let strategy = &mut ctx.accounts.strategy;
+        strategy.current_debt = strategy.current_debt.checked_sub(loss).unwrap();
+        strategy.total_value = strategy.total_value.min(strategy.current_debt);
+        let vault = &mut ctx.accounts.vault;
+        vault.strategy_debt = vault.strategy_debt.checked_sub(loss).unwrap();
+        vault.release_locked_profit(loss, Clock::get()?.unix_timestamp);
+
+        emit_event!(ctx.accounts, StrategyLoss {
+            vault: vault.key(),
+            strategy: strategy.key(),
+            loss,
+            total_backing: vault.total_backing(),
+        });
+
+        Ok(())
+    }
+
+    // Keeper crank moving up to `amount` from a strategy above its target
+    // weight to one below it. The first `from_accounts` remaining accounts go
+    // to the source adapter, the rest to the destination adapter
+    pub fn rebalance<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Rebalance<'info>>,
+        amount: u64,
+        from_accounts: u8,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(!vault.shutdown, ErrorCode::VaultShutdown);
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            ctx.accounts.from_strategy.key() != ctx.accounts.to_strategy.key(),
+            ErrorCode::InvalidRebalance
+        );
+        require!(
+            (from_accounts as usize) <= ctx.remaining_accounts.len(),
+            ErrorCode::InvalidRebalance
+        );
+
+        // Move no more than the source's excess, the destination's shortfall
+        // and the per-call cap allow
+        let backing = vault.total_backing();
+        let from = &ctx.accounts.from_strategy;
+        let to = &ctx.accounts.to_strategy;
+        let excess = from
+            .current_debt
+            .saturating_sub(bps_of(backing, from.debt_ratio_bps));
+        let shortfall = bps_of(backing, to.debt_ratio_bps)
+            .min(to.max_debt)
+            .saturating_sub(to.current_debt);
+        let amount = amount
+            .min(excess)
+            .min(shortfall)
+            .min(bps_of(backing, vault.rebalance_cap_bps));
+        require!(amount > 0, ErrorCode::InvalidRebalance);
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let (from_remaining, to_remaining) =
+            ctx.remaining_accounts.split_at(from_accounts as usize);
+        let vault_signer = ctx.accounts.vault_signer.key();
+        ctx.accounts.from_strategy.check_accounts(vault_signer, from_remaining)?;
+        ctx.accounts.to_strategy.check_accounts(vault_signer, to_remaining)?;
+
+        let before = ctx.accounts.vault_usdc_account.amount;
+        invoke_adapter(
+            &ctx.accounts.from_adapter_program,
+            "withdraw",
+            amount,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            from_remaining,
+            &[&seeds[..]],
+        )?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        let received = ctx.accounts.vault_usdc_account.amount.checked_sub(before).unwrap();
+
+        // Only what actually came back is redeployed
+        let before = ctx.accounts.vault_usdc_account.amount;
+        invoke_adapter(
+            &ctx.accounts.to_adapter_program,
+            "deposit",
+            received,
+            &ctx.accounts.vault_signer,
+            &ctx.accounts.vault_usdc_account.to_account_info(),
+            to_remaining,
+            &[&seeds[..]],
+        )?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        let spent = before.checked_sub(ctx.accounts.vault_usdc_account.amount).unwrap();
+        require!(spent <= received, ErrorCode::AdapterAmountMismatch);
+
+        let from = &mut ctx.accounts.from_strategy;
+        let repaid = received.min(from.current_debt);
+        let realized = (received - repaid).min(from.unrealized_gain);
+        from.current_debt = from.current_debt.checked_sub(repaid).unwrap();
+        from.unrealized_gain = from.unrealized_gain.checked_sub(realized).unwrap();
+        from.total_value = from.total_value.saturating_sub(received);
+
+        let to = &mut ctx.accounts.to_strategy;
+        to.current_debt = to.current_debt.checked_add(spent).unwrap();
+        to.total_value = to.total_value.checked_add(spent).unwrap();
+
+        // Rounding dust left over stays in the vault as liquid USDC
+        let vault = &mut ctx.accounts.vault;
+        vault.strategy_debt = vault
+            .strategy_debt
+            .checked_sub(repaid)
+            .unwrap()
+            .checked_add(spent)
+            .unwrap();
+        vault.strategy_gain = vault.strategy_gain.checked_sub(realized).unwrap();
+        vault.total_usdc = vault
+            .total_usdc
+            .checked_add(received.checked_sub(spent).unwrap())
+            .unwrap();
+
+        emit_event!(ctx.accounts, StrategyRebalanced {
+            vault: vault.key(),
+            from_strategy: from.key(),
+            to_strategy: to.key(),
+            withdrawn: received,
+            deployed: spent,
+        });
+
+        Ok(())
+    }
+
+    // Swap reward tokens held by the vault signer into USDC through a
+    // whitelisted router and book the proceeds as profit. `data` is the router
+    // instruction and the remaining accounts are its accounts
+    pub fn compound<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Compound<'info>>,
+        amount: u64,
+        min_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        // The vault signer signs the swap, so the router must not see any
+        // other token account it owns
+        let vault_signer = ctx.accounts.vault_signer.key();
+        let reward_account = ctx.accounts.reward_account.key();
+        let vault_usdc_account = ctx.accounts.vault_usdc_account.key();
+        for account in ctx.remaining_accounts {
+            if (account.owner == &token::ID || account.owner == &Token2022::id())
+                && account.data_len() >= TokenAccount::LEN
+            {
+                let owner = Pubkey::new_from_array(
+                    account.try_borrow_data()?[32..64].try_into().unwrap(),
+                );
+                require!(
+                    owner != vault_signer
+                        || account.key() == reward_account
+                        || account.key() == vault_usdc_account,
+                    ErrorCode::InvalidRouterAccounts
+                );
+            }
+        }
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                let is_signer = account.is_signer || account.key() == vault_signer;
+                if account.is_writable {
+                    AccountMeta::new(account.key(), is_signer)
+                } else {
+                    AccountMeta::new_readonly(account.key(), is_signer)
+                }
+            })
+            .collect();
+        let mut infos = vec![
+            ctx.accounts.router_program.to_account_info(),
+            ctx.accounts.vault_signer.to_account_info(),
+        ];
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let reward_before = ctx.accounts.reward_account.amount;
+        let usdc_before = ctx.accounts.vault_usdc_account.amount;
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.router_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.reward_account.reload()?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        // The router ran with the vault signer's authority, so it must not
+        // have left a delegate or close authority on the vault's accounts
+        for account in [&ctx.accounts.reward_account, &ctx.accounts.vault_usdc_account] {
+            require!(
+                account.delegate.is_none() && account.close_authority.is_none(),
+                ErrorCode::InvalidRouterAccounts
+            );
+        }
+        let spent = reward_before.saturating_sub(ctx.accounts.reward_account.amount);
+        let received = ctx.accounts.vault_usdc_account.amount.saturating_sub(usdc_before);
+        require!(
+            spent <= amount && ctx.accounts.vault_usdc_account.amount >= usdc_before,
+            ErrorCode::InvalidRouterAccounts
+        );
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+
+        // Proceeds unlock into share pricing like any other profit
+        let vault = &mut ctx.accounts.vault;
+        let fee = vault.book_profit(received, ctx.accounts.asset.price);
+        vault.lock_profit(received - fee, Clock::get()?.unix_timestamp);
+
+        emit_event!(ctx.accounts, Compounded {
+            vault: vault.key(),
+            reward_mint: ctx.accounts.reward_account.mint,
+            amount_in: spent,
+            usdc_out: received,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    pub fn whitelist_router(ctx: Context<WhitelistRouter>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.router_program.executable,
+            ErrorCode::RouterNotWhitelisted
+        );
+
+        ctx.accounts.router_entry.program = ctx.accounts.router_program.key();
+        Ok(())
+    }
+
+    pub fn remove_router(ctx: Context<RemoveRouter>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn set_withdraw_cap(ctx: Context<UpdateVault>, withdraw_cap_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            withdraw_cap_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidWithdrawCap
+        );
+
+        emit_event!(ctx.accounts, VaultLimitUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultLimit::WithdrawCap,
+            old_value: ctx.accounts.vault.withdraw_cap_bps as u64,
+            new_value: withdraw_cap_bps as u64,
+        });
+        ctx.accounts.vault.withdraw_cap_bps = withdraw_cap_bps;
+
+        Ok(())
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.vault = ctx.accounts.vault.key();
+        position.owner = ctx.accounts.owner.key();
+        position.deposited = 0;
+        position.last_deposit_slot = 0;
+        position.redeemed = 0;
+        position.share_high_water_mark = 0;
+        position.first_activity_slot = 0;
+        position.last_activity_slot = 0;
+        position.twab_balance = 0;
+        position.twab_cumulative = 0;
+        position.twab_ts = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // Permissionless: record the owner's current share ATA balance, so
+    // transfers in and out are reflected in the time-weighted balance
+    pub fn sync_position_balance(ctx: Context<SyncPositionBalance>) -> Result<()> {
+        ctx.accounts.position.observe_balance(
+            ctx.accounts.share_account.amount,
+            Clock::get()?.unix_timestamp,
+        );
+        Ok(())
+    }
+
+    // Return the rent of an emptied position. Positions carry the cumulative
+    // deposits behind the per-user cap, so they stay open while a cap is set
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        require!(
+            ctx.accounts.share_account.amount == 0,
+            ErrorCode::PositionNotEmpty
+        );
+        require!(
+            ctx.accounts.vault.per_user_cap == 0,
+            ErrorCode::PositionInUse
+        );
+        Ok(())
+    }
+
+    // Yield distribution as an alternative to price-per-share accrual: holders
+    // stake shares into the pool and claim USDC pushed into it pro-rata
+    pub fn init_reward_pool(ctx: Context<InitRewardPool>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.vault = ctx.accounts.vault.key();
+        pool.reward_per_share = 0;
+        pool.total_staked = 0;
+        pool.total_boosted = 0;
+        pool.total_distributed = 0;
+        pool.reward_mint = Pubkey::default();
+        pool.emission_rate = 0;
+        pool.emission_per_share = 0;
+        pool.emission_ts = Clock::get()?.unix_timestamp;
+        pool.emissions_remaining = 0;
+        pool.bump = ctx.bumps.reward_pool;
+        Ok(())
+    }
+
+    pub fn open_yield_checkpoint(ctx: Context<OpenYieldCheckpoint>) -> Result<()> {
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        checkpoint.vault = ctx.accounts.vault.key();
+        checkpoint.owner = ctx.accounts.owner.key();
+        checkpoint.staked = 0;
+        checkpoint.reward_per_share_paid = ctx.accounts.reward_pool.reward_per_share;
+        checkpoint.accrued = 0;
+        checkpoint.emission_per_share_paid = ctx.accounts.reward_pool.emission_per_share;
+        checkpoint.rewards_accrued = 0;
+        checkpoint.lock_tier = LockTier::None;
+        checkpoint.lock_end = 0;
+        checkpoint.boosted = 0;
+        Ok(())
+    }
+
+    pub fn stake_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StakeShares<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(amount > 0, ErrorCode::InvalidStakeAmount);
+
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.owner_share_account.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.pool_share_account.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            &[],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        pool.accrue_emissions(now);
+        checkpoint.settle(pool);
+        checkpoint.staked = checkpoint.staked.checked_add(amount).unwrap();
+        pool.total_staked = pool.total_staked.checked_add(amount).unwrap();
+        checkpoint.update_boost(pool, now);
+
+        emit_event!(ctx.accounts, StakeUpdated {
+            vault: ctx.accounts.vault.key(),
+            owner: checkpoint.owner,
+            staked: checkpoint.staked,
+            total_staked: pool.total_staked,
+        });
+
+        Ok(())
+    }
+
+    pub fn unstake_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StakeShares<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidStakeAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        require!(amount <= checkpoint.staked, ErrorCode::InsufficientStake);
+        // Locked stakes can only leave early if the vault is shut down
+        require!(
+            now >= checkpoint.lock_end || ctx.accounts.vault.shutdown,
+            ErrorCode::StakeStillLocked
+        );
+        pool.accrue_emissions(now);
+        checkpoint.settle(pool);
+        checkpoint.staked = checkpoint.staked.checked_sub(amount).unwrap();
+        pool.total_staked = pool.total_staked.checked_sub(amount).unwrap();
+        checkpoint.update_boost(pool, now);
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[b"reward_pool".as_ref(), vault_key.as_ref(), &[pool.bump]];
+        let signer = &[&seeds[..]];
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.pool_share_account.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.owner_share_account.to_account_info(),
+            &pool.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer,
+        )?;
+
+        emit_event!(ctx.accounts, StakeUpdated {
+            vault: vault_key,
+            owner: checkpoint.owner,
+            staked: checkpoint.staked,
+            total_staked: pool.total_staked,
+        });
+
+        Ok(())
+    }
+
+    // Push USDC backing above what the shares are worth at the current price
+    // into the reward pool
+    pub fn distribute_yield(ctx: Context<DistributeYield>, amount: u64) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        require!(pool.total_staked > 0, ErrorCode::NoStakedShares);
+        let surplus = vault
+            .share_backing(Clock::get()?.unix_timestamp)
+            .saturating_sub(vault.shares_value(asset.price));
+        require!(
+            amount > 0 && amount <= surplus.min(vault.total_usdc),
+            ErrorCode::InsufficientYield
+        );
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.pool_usdc_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+
+        // Stakers are credited only what reaches the pool after any transfer fee
+        let received = amount
+            .checked_sub(transfer_fee(&ctx.accounts.usdc_mint, amount)?)
+            .unwrap();
+        vault.total_usdc = vault.total_usdc.checked_sub(amount).unwrap();
+        pool.reward_per_share = pool
+            .reward_per_share
+            .checked_add(
+                (received as u128)
+                    .checked_mul(REWARD_INDEX_SCALE)
+                    .unwrap()
+                    .checked_div(pool.total_staked as u128)
+                    .unwrap(),
+            )
+            .unwrap();
+        pool.total_distributed = pool.total_distributed.checked_add(received).unwrap();
+
+        emit_event!(ctx.accounts, YieldDistributed {
+            vault: vault.key(),
+            amount: received,
+            total_staked: pool.total_staked,
+            reward_per_share: pool.reward_per_share,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_yield(ctx: Context<ClaimYield>) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+
+        let pool = &ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        checkpoint.settle(pool);
+        let amount = checkpoint.accrued;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        checkpoint.accrued = 0;
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[b"reward_pool".as_ref(), vault_key.as_ref(), &[pool.bump]];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_usdc_account.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+                to: ctx.accounts.owner_usdc_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.usdc_mint.decimals)?;
+        Ok(())
+    }
+
+    pub fn close_yield_checkpoint(ctx: Context<CloseYieldCheckpoint>) -> Result<()> {
+        let checkpoint = &ctx.accounts.checkpoint;
+        require!(
+            checkpoint.staked == 0 && checkpoint.accrued == 0 && checkpoint.rewards_accrued == 0,
+            ErrorCode::PositionNotEmpty
+        );
+        Ok(())
+    }
+
+    // Reward token emissions to staked shares, alongside the USDC yield index
+    pub fn init_emissions(ctx: Context<InitEmissions>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        require!(
+            pool.reward_mint == Pubkey::default(),
+            ErrorCode::EmissionsAlreadyInitialized
+        );
+        pool.reward_mint = ctx.accounts.reward_mint.key();
+        pool.emission_ts = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // Top up the emissions account and set the per-second rate; emissions
+    // stop once the funded amount has been handed out
+    pub fn fund_emissions(
+        ctx: Context<FundEmissions>,
+        amount: u64,
+        emission_rate: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.accrue_emissions(Clock::get()?.unix_timestamp);
+
+        if amount > 0 {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.funder_reward_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.emissions_account.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            );
+            token_interface::transfer_checked(
+                transfer_ctx,
+                amount,
+                ctx.accounts.reward_mint.decimals,
+            )?;
+            // Only what lands after any transfer fee can be emitted
+            let received = amount
+                .checked_sub(transfer_fee(&ctx.accounts.reward_mint, amount)?)
+                .unwrap();
+            pool.emissions_remaining = pool.emissions_remaining.checked_add(received).unwrap();
+        }
+        pool.emission_rate = emission_rate;
+
+        emit_event!(ctx.accounts, EmissionsFunded {
+            vault: ctx.accounts.vault.key(),
+            amount,
+            emission_rate,
+            emissions_remaining: pool.emissions_remaining,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        pool.accrue_emissions(now);
+        checkpoint.settle(pool);
+        checkpoint.update_boost(pool, now);
+        let amount = checkpoint.rewards_accrued;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        checkpoint.rewards_accrued = 0;
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[b"reward_pool".as_ref(), vault_key.as_ref(), &[pool.bump]];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.emissions_account.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                to: ctx.accounts.owner_reward_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+        Ok(())
+    }
+
+    // Lock the whole stake for a fixed term in exchange for boosted emissions;
+    // a live lock can only be extended to an equal or longer tier
+    pub fn lock_stake(ctx: Context<LockStake>, tier: LockTier) -> Result<()> {
+        require!(tier != LockTier::None, ErrorCode::InvalidLockTier);
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        require!(checkpoint.staked > 0, ErrorCode::InsufficientStake);
+        require!(
+            now >= checkpoint.lock_end || tier.duration() >= checkpoint.lock_tier.duration(),
+            ErrorCode::InvalidLockTier
+        );
+
+        pool.accrue_emissions(now);
+        checkpoint.settle(pool);
+        checkpoint.lock_tier = tier;
+        checkpoint.lock_end = now.checked_add(tier.duration()).unwrap();
+        checkpoint.update_boost(pool, now);
+
+        emit_event!(ctx.accounts, StakeLocked {
+            vault: ctx.accounts.vault.key(),
+            owner: checkpoint.owner,
+            tier,
+            lock_end: checkpoint.lock_end,
+            boosted: checkpoint.boosted,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: drop the boost of a lock that has run out
+    pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        require!(
+            checkpoint.lock_tier != LockTier::None && now >= checkpoint.lock_end,
+            ErrorCode::StakeStillLocked
+        );
+
+        pool.accrue_emissions(now);
+        checkpoint.settle(pool);
+        checkpoint.update_boost(pool, now);
+        Ok(())
+    }
+
+    // A gauge lets ve holders steer emissions towards a vault and escrows the
+    // vault's shares locked for voting power
+    pub fn create_gauge(ctx: Context<CreateGauge>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let gauge = &mut ctx.accounts.gauge;
+        gauge.vault = ctx.accounts.vault.key();
+        gauge.weight = 0;
+        gauge.bump = ctx.bumps.gauge;
+        Ok(())
+    }
+
+    // Voting power is the locked amount scaled by the time left on the lock,
+    // so it decays linearly to zero at unlock and cannot be transferred
+    pub fn lock_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LockShares<'info>>,
+        amount: u64,
+        duration: i64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(amount > 0, ErrorCode::InvalidStakeAmount);
+        require!(
+            (MIN_VE_LOCK_DURATION..=MAX_VE_LOCK_DURATION).contains(&duration),
+            ErrorCode::InvalidLockDuration
+        );
+
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.owner_share_account.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.ve_escrow.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            &[],
+        )?;
+
+        let ve_lock = &mut ctx.accounts.ve_lock;
+        ve_lock.vault = ctx.accounts.vault.key();
+        ve_lock.owner = ctx.accounts.owner.key();
+        ve_lock.amount = amount;
+        ve_lock.lock_end = Clock::get()?.unix_timestamp.checked_add(duration).unwrap();
+        ve_lock.voted_vault = Pubkey::default();
+        ve_lock.vote_weight = 0;
+        ve_lock.bump = ctx.bumps.ve_lock;
+
+        emit_event!(ctx.accounts, SharesLocked {
+            vault: ve_lock.vault,
+            owner: ve_lock.owner,
+            amount,
+            lock_end: ve_lock.lock_end,
+        });
+
+        Ok(())
+    }
+
+    pub fn unlock_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockShares<'info>>,
+    ) -> Result<()> {
+        let ve_lock = &ctx.accounts.ve_lock;
+        require!(
+            Clock::get()?.unix_timestamp >= ve_lock.lock_end,
+            ErrorCode::StakeStillLocked
+        );
+        require!(ve_lock.vote_weight == 0, ErrorCode::VoteActive);
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            b"ve_authority".as_ref(),
+            vault_key.as_ref(),
+            &[ctx.bumps.ve_authority],
+        ];
+        let signer = &[&seeds[..]];
+        transfer_shares(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.ve_escrow.to_account_info(),
+            &ctx.accounts.asset_mint,
+            &ctx.accounts.owner_share_account.to_account_info(),
+            &ctx.accounts.ve_authority.to_account_info(),
+            ctx.remaining_accounts,
+            ve_lock.amount,
+            signer,
+        )?;
+        Ok(())
+    }
+
+    // Put the lock's current voting power behind one gauge; switching gauges
+    // takes a reset_vote first
+    pub fn vote_gauge(ctx: Context<VoteGauge>) -> Result<()> {
+        let gauge = &mut ctx.accounts.gauge;
+        let ve_lock = &mut ctx.accounts.ve_lock;
+        require!(
+            ve_lock.vote_weight == 0 || ve_lock.voted_vault == gauge.vault,
+            ErrorCode::VoteActive
+        );
+
+        let power = ve_lock.voting_power(Clock::get()?.unix_timestamp);
+        require!(power > 0, ErrorCode::NoVotingPower);
+        ve_lock.set_vote(gauge, &mut ctx.accounts.config, power);
+
+        emit_event!(ctx.accounts, GaugeVoted {
+            gauge_vault: gauge.vault,
+            owner: ve_lock.owner,
+            weight: power,
+            gauge_weight: gauge.weight,
+        });
+
+        Ok(())
+    }
+
+    pub fn reset_vote(ctx: Context<ResetVote>) -> Result<()> {
+        let gauge = &mut ctx.accounts.gauge;
+        let ve_lock = &mut ctx.accounts.ve_lock;
+        ve_lock.set_vote(gauge, &mut ctx.accounts.config, 0);
+
+        emit_event!(ctx.accounts, GaugeVoted {
+            gauge_vault: gauge.vault,
+            owner: ve_lock.owner,
+            weight: 0,
+            gauge_weight: gauge.weight,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: decay a standing vote down to the lock's current power
+    pub fn refresh_vote(ctx: Context<RefreshVote>) -> Result<()> {
+        let gauge = &mut ctx.accounts.gauge;
+        let ve_lock = &mut ctx.accounts.ve_lock;
+        let power = ve_lock.voting_power(Clock::get()?.unix_timestamp);
+        ve_lock.set_vote(gauge, &mut ctx.accounts.config, power);
+
+        emit_event!(ctx.accounts, GaugeVoted {
+            gauge_vault: gauge.vault,
+            owner: ve_lock.owner,
+            weight: power,
+            gauge_weight: gauge.weight,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: point the vault's emissions at its share of the gauge
+    // emission rate
+    pub fn apply_gauge(ctx: Context<ApplyGauge>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let gauge = &ctx.accounts.gauge;
+        let emission_rate = if config.gauge_total_weight == 0 {
+            0
+        } else {
+            (config.gauge_emission_rate as u128)
+                .checked_mul(gauge.weight as u128)
+                .unwrap()
+                .checked_div(config.gauge_total_weight as u128)
+                .unwrap() as u64
+        };
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.accrue_emissions(Clock::get()?.unix_timestamp);
+        pool.emission_rate = emission_rate;
+
+        emit_event!(ctx.accounts, GaugeApplied {
+            vault: gauge.vault,
+            weight: gauge.weight,
+            total_weight: config.gauge_total_weight,
+            emission_rate,
+        });
+
+        Ok(())
+    }
+
+    // Fund a merkle-root campaign of (wallet, amount) entries computed off-chain
+    pub fn create_distribution(
+        ctx: Context<CreateDistribution>,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            merkle_root != [0; 32] && total_amount > 0,
+            ErrorCode::InvalidDistribution
+        );
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.distribution_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        let vault = &mut ctx.accounts.vault;
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.vault = vault.key();
+        distribution.nonce = vault.distribution_nonce;
+        distribution.mint = ctx.accounts.mint.key();
+        distribution.merkle_root = merkle_root;
+        distribution.total_amount = total_amount;
+        distribution.claimed_amount = 0;
+        distribution.bump = ctx.bumps.distribution;
+
+        vault.distribution_nonce = vault.distribution_nonce.checked_add(1).unwrap();
+
+        emit_event!(ctx.accounts, DistributionCreated {
+            vault: vault.key(),
+            distribution: distribution.key(),
+            mint: distribution.mint,
+            merkle_root,
+            total_amount,
+        });
+
+        Ok(())
+    }
+
+    // The claim record is created on first claim, so each entry pays out once
+    pub fn claim_distribution(
+        ctx: Context<ClaimDistribution>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+
+        let claimant = ctx.accounts.claimant.key();
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            distribution.contains(claimant, amount, &proof),
+            ErrorCode::InvalidMerkleProof
+        );
+        distribution.claimed_amount = distribution.claimed_amount.checked_add(amount).unwrap();
+        require!(
+            distribution.claimed_amount <= distribution.total_amount,
+            ErrorCode::DistributionExhausted
+        );
+
+        let claim = &mut ctx.accounts.claim;
+        claim.distribution = distribution.key();
+        claim.wallet = claimant;
+        claim.amount = amount;
+
+        let vault_key = ctx.accounts.vault.key();
+        let nonce = distribution.nonce.to_le_bytes();
+        let seeds = &[
+            b"distribution".as_ref(),
+            vault_key.as_ref(),
+            nonce.as_ref(),
+            &[distribution.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.distribution_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.claimant_token_account.to_account_info(),
+                authority: distribution.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit_event!(ctx.accounts, DistributionClaimed {
+            distribution: distribution.key(),
+            wallet: claimant,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.wallet = ctx.accounts.wallet.key();
+        referrer.lifetime_volume = 0;
+        referrer.lifetime_rewards = 0;
+        Ok(())
+    }
+
+    pub fn propose_asset_authority(
+        ctx: Context<UpdateAsset>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        asset.pending_authority = new_authority;
+        Ok(())
+    }
+
+    pub fn accept_asset_authority(ctx: Context<AcceptAssetAuthority>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.pending_authority != Pubkey::default()
+                && asset.pending_authority == ctx.accounts.new_authority.key(),
+            ErrorCode::NotPendingAuthority
+        );
+
+        emit_event!(ctx.accounts, AssetAuthorityTransferred {
+            asset: asset.key(),
+            old_authority: asset.authority,
+            new_authority: asset.pending_authority,
+        });
+        asset.authority = asset.pending_authority;
+        asset.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    // Mark an asset as an official listing; only the protocol admin can
+    // vouch for one
+    pub fn set_verification(ctx: Context<SetVerification>, verified: bool) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let asset = &mut ctx.accounts.asset;
+        asset.verified = verified;
+        emit_event!(ctx.accounts, AssetVerificationSet {
+            asset: asset.key(),
+            verified,
+        });
+        Ok(())
+    }
+
+    // Forfeit a malicious listing's bond to the treasury; the bond account
+    // closes there, so the creator has nothing left to reclaim
+    pub fn slash_bond(ctx: Context<SlashBond>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let bond = &ctx.accounts.bond;
+        emit_event!(ctx.accounts, BondSlashed {
+            asset: bond.asset,
+            creator: bond.creator,
+            amount: bond.amount,
+            treasury: ctx.accounts.treasury.key(),
+        });
+        Ok(())
+    }
+
+    // One-step handover by the current asset authority. The protocol admin
+    // has no say here, and a renounced asset has no signer left to call it
+    pub fn transfer_asset_authority(
+        ctx: Context<TransferAssetAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        emit_event!(ctx.accounts, AssetAuthorityTransferred {
+            asset: asset.key(),
+            old_authority: asset.authority,
+            new_authority,
+        });
+        asset.authority = new_authority;
+        asset.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn set_asset_governance(ctx: Context<SetAssetGovernance>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Governance accounts are PDAs of the governance program, so approved
+        // proposals can sign asset-scoped instructions on the realm's behalf
+        let governance_program = &ctx.accounts.governance_program;
+        require!(
+            governance_program.executable
+                && *ctx.accounts.governance.owner == governance_program.key(),
+            ErrorCode::InvalidGovernance
+        );
+
+        emit_event!(ctx.accounts, AssetAuthorityTransferred {
+            asset: asset.key(),
+            old_authority: asset.authority,
+            new_authority: ctx.accounts.governance.key(),
+        });
+        asset.authority = ctx.accounts.governance.key();
+        asset.pending_authority = Pubkey::default();
+        asset.governance_program = governance_program.key();
+        Ok(())
+    }
+
+    pub fn update_asset_metadata(
+        ctx: Context<UpdateAssetMetadata>,
+        name: String,
+        ticker: String,
+        uri: String,
+    ) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(name.len() <= 50, ErrorCode::NameTooLong);
+        require!(ticker.len() <= 10, ErrorCode::TickerTooLong);
+        require!(uri.len() <= MAX_URI_LEN, ErrorCode::UriTooLong);
+        // The ticker claim pins the symbol; only its spelling can change
+        require!(
+            normalize_ticker(&ticker) == normalize_ticker(&asset.ticker),
+            ErrorCode::TickerImmutable
+        );
+
+        asset.name = name;
+        asset.ticker = ticker;
+        asset.uri = uri;
+        Ok(())
+    }
+
+    // Category and tag bits let integrators group vaults on-chain; the tag
+    // bits carry no meaning to the program
+    pub fn set_asset_category(
+        ctx: Context<UpdateAsset>,
+        category: AssetCategory,
+        tags: u16,
+    ) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        asset.category = category;
+        asset.tags = tags;
+        Ok(())
+    }
+
+    // Copy the asset's name, ticker and URI into the share mint's embedded
+    // Token-2022 metadata, initializing it on first use
+    pub fn sync_share_metadata(ctx: Context<SyncShareMetadata>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &ctx.accounts.vault;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // TLV header, update authority, mint, three strings and an empty list
+        let metadata_len = |name: &str, symbol: &str, uri: &str| {
+            4 + 32 + 32 + 4 + name.len() + 4 + symbol.len() + 4 + uri.len() + 4
+        };
+        let mint = ctx.accounts.asset_mint.to_account_info();
+        let existing = {
+            let data = mint.try_borrow_data()?;
+            StateWithExtensions::<Token2022Mint>::unpack(&data)?
+                .get_variable_len_extension::<TokenMetadata>()
+                .ok()
+        };
+        let current_len = existing
+            .as_ref()
+            .map_or(0, |metadata| metadata_len(&metadata.name, &metadata.symbol, &metadata.uri));
+
+        // Fund the rent for the resized mint before the token program reallocates it
+        let new_len = mint.data_len() - current_len
+            + metadata_len(&asset.name, &asset.ticker, &asset.uri);
+        let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(mint.lamports());
+        if top_up > 0 {
+            let rent_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: mint.clone(),
+                },
+            );
+            system_program::transfer(rent_ctx, top_up)?;
+        }
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        if existing.is_none() {
+            let metadata_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenMetadataInitialize {
+                    program_id: ctx.accounts.token_program.to_account_info(),
+                    metadata: mint.clone(),
+                    update_authority: ctx.accounts.vault_signer.to_account_info(),
+                    mint_authority: ctx.accounts.vault_signer.to_account_info(),
+                    mint: mint.clone(),
+                },
+                signer,
+            );
+            return token_interface::token_metadata_initialize(
+                metadata_ctx,
+                asset.name.clone(),
+                asset.ticker.clone(),
+                asset.uri.clone(),
+            );
+        }
+
+        for (field, value) in [
+            (Field::Name, &asset.name),
+            (Field::Symbol, &asset.ticker),
+            (Field::Uri, &asset.uri),
+        ] {
+            let update_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TokenMetadataUpdateField {
+                    program_id: ctx.accounts.token_program.to_account_info(),
+                    metadata: mint.clone(),
+                    update_authority: ctx.accounts.vault_signer.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::token_metadata_update_field(update_ctx, field, value.clone())?;
+        }
+
+        Ok(())
+    }
+
+    // Turn the vault into an index vault: USDC deposits buy into a basket of
+    // tokens held at target weights, and shares are priced off the oracle
+    // value of the basket
+    pub fn init_basket(ctx: Context<InitBasket>, band_bps: u16) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(band_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidBasket);
+
+        let basket = &mut ctx.accounts.basket;
+        basket.vault = ctx.accounts.vault.key();
+        basket.band_bps = band_bps;
+        basket.components = Vec::new();
+
+        ctx.accounts.vault.index_vault = true;
+        Ok(())
+    }
+
+    // Whatever weight the components leave unassigned stays in USDC
+    pub fn add_basket_component(
+        ctx: Context<AddBasketComponent>,
+        target_weight_bps: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+
+        let vault = &ctx.accounts.vault;
+        let basket = &mut ctx.accounts.basket;
+        let mint = ctx.accounts.component_mint.key();
+        require!(
+            basket.components.len() < MAX_BASKET_COMPONENTS
+                && mint != vault.underlying_mint
+                && basket.components.iter().all(|component| component.mint != mint),
+            ErrorCode::InvalidBasket
+        );
+        let total_weight = basket
+            .components
+            .iter()
+            .map(|component| component.target_weight_bps as u64)
+            .sum::<u64>()
+            .checked_add(target_weight_bps as u64)
+            .unwrap();
+        require!(total_weight <= BPS_DENOMINATOR, ErrorCode::InvalidBasket);
+
+        // The feed has to be readable now, not just at the first mark
+        let price = oracle_price(&ctx.accounts.oracle, vault.underlying_decimals)?;
+
+        basket.components.push(BasketComponent {
+            mint,
+            token_account: ctx.accounts.vault_component_account.key(),
+            oracle: ctx.accounts.oracle.key(),
+            decimals: ctx.accounts.component_mint.decimals,
+            target_weight_bps,
+            balance: 0,
+            price,
+        });
+        Ok(())
+    }
+
+    // Permissionless: re-price every component from its oracle and reset the
+    // share price to the vault's net asset value. The oracles are passed as
+    // remaining accounts in component order
+    pub fn mark_basket(ctx: Context<MarkBasket>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &mut ctx.accounts.vault;
+        let basket = &mut ctx.accounts.basket;
+        require!(!ctx.accounts.asset.delisted, ErrorCode::AssetDelisted);
+        require!(
+            ctx.remaining_accounts.len() == basket.components.len(),
+            ErrorCode::InvalidOracle
+        );
+
+        let mut value: u64 = 0;
+        for (component, oracle) in basket.components.iter_mut().zip(ctx.remaining_accounts) {
+            require!(oracle.key() == component.oracle, ErrorCode::InvalidOracle);
+            component.price = oracle_price(oracle, vault.underlying_decimals)?;
+            value = value.checked_add(component.value()).unwrap();
+        }
+        vault.basket_value = value;
+        vault.basket_marked_at = now;
+
+        let supply = vault.total_assets.checked_add(vault.fee_shares_outstanding).unwrap();
+        if supply > 0 {
+            let price = (vault.share_backing(now) as u128)
+                .checked_mul(vault.unit() as u128)
+                .unwrap()
+                .checked_div(supply as u128)
+                .unwrap() as u64;
+            require!(price > 0, ErrorCode::InvalidPrice);
+            ctx.accounts.asset.price = price;
+        }
+
+        emit_event!(ctx.accounts, BasketMarked {
+            vault: vault.key(),
+            basket_value: value,
+            price: ctx.accounts.asset.price,
+        });
+
+        Ok(())
+    }
+
+    // Swap between vault USDC and one component through a whitelisted router
+    // to bring a component that drifted outside its band back inside it.
+    // `buy` spends USDC on the component, otherwise the component is sold
+    pub fn rebalance_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RebalanceBasket<'info>>,
+        index: u8,
+        buy: bool,
+        amount_in: u64,
+        min_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::Keeper,
+                ctx.accounts.role_grant.as_deref()
+            ) || vault.is_automation_thread(ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        vault.check_basket_fresh(now)?;
+
+        let basket = &ctx.accounts.basket;
+        let component = *basket
+            .components
+            .get(index as usize)
+            .ok_or(ErrorCode::InvalidBasket)?;
+        require!(
+            ctx.accounts.vault_component_account.key() == component.token_account,
+            ErrorCode::InvalidBasket
+        );
+        let nav = vault.total_backing();
+        let drift_before = basket.drift_bps(&component, nav);
+        require!(
+            drift_before.unsigned_abs() > basket.band_bps as u64,
+            ErrorCode::BasketWithinBand
+        );
+        require!(buy == (drift_before < 0), ErrorCode::InvalidRebalance);
+
+        // The vault signer signs the swap, so the router must not see any
+        // other token account it owns
+        let vault_signer = ctx.accounts.vault_signer.key();
+        let vault_component_account = ctx.accounts.vault_component_account.key();
+        let vault_usdc_account = ctx.accounts.vault_usdc_account.key();
+        for account in ctx.remaining_accounts {
+            if (account.owner == &token::ID || account.owner == &Token2022::id())
+                && account.data_len() >= TokenAccount::LEN
+            {
+                let owner = Pubkey::new_from_array(
+                    account.try_borrow_data()?[32..64].try_into().unwrap(),
+                );
+                require!(
+                    owner != vault_signer
+                        || account.key() == vault_component_account
+                        || account.key() == vault_usdc_account,
+                    ErrorCode::InvalidRouterAccounts
+                );
+            }
+        }
+
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                let is_signer = account.is_signer || account.key() == vault_signer;
+                if account.is_writable {
+                    AccountMeta::new(account.key(), is_signer)
+                } else {
+                    AccountMeta::new_readonly(account.key(), is_signer)
+                }
+            })
+            .collect();
+        let mut infos = vec![
+            ctx.accounts.router_program.to_account_info(),
+            ctx.accounts.vault_signer.to_account_info(),
+        ];
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            ctx.accounts.asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let component_before = ctx.accounts.vault_component_account.amount;
+        let usdc_before = ctx.accounts.vault_usdc_account.amount;
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.router_program.key(),
+                accounts,
+                data,
+            },
+            &infos,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.vault_component_account.reload()?;
+        ctx.accounts.vault_usdc_account.reload()?;
+        let component_after = ctx.accounts.vault_component_account.amount;
+        let usdc_after = ctx.accounts.vault_usdc_account.amount;
+        let (spent, received) = if buy {
+            require!(component_after >= component_before, ErrorCode::InvalidRouterAccounts);
+            (
+                usdc_before.saturating_sub(usdc_after),
+                component_after - component_before,
+            )
+        } else {
+            require!(usdc_after >= usdc_before, ErrorCode::InvalidRouterAccounts);
+            (
+                component_before.saturating_sub(component_after),
+                usdc_after - usdc_before,
+            )
+        };
+        require!(spent <= amount_in, ErrorCode::InvalidRouterAccounts);
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+
+        let vault = &mut ctx.accounts.vault;
+        let basket = &mut ctx.accounts.basket;
+        let component = &mut basket.components[index as usize];
+        let value_before = component.value();
+        if buy {
+            vault.total_usdc = vault.total_usdc.checked_sub(spent).unwrap();
+            component.balance = component.balance.checked_add(received).unwrap();
+        } else {
+            vault.total_usdc = vault.total_usdc.checked_add(received).unwrap();
+            component.balance = component.balance.checked_sub(spent).unwrap();
+        }
+        vault.basket_value = vault
+            .basket_value
+            .checked_sub(value_before)
+            .unwrap()
+            .checked_add(component.value())
+            .unwrap();
+
+        // The trade has to land inside the band rather than overshoot it
+        let component = basket.components[index as usize];
+        let drift_after = basket.drift_bps(&component, vault.total_backing());
+        require!(
+            drift_after.unsigned_abs() <= basket.band_bps as u64,
+            ErrorCode::InvalidRebalance
+        );
+
+        emit_event!(ctx.accounts, BasketRebalanced {
+            vault: vault.key(),
+            mint: component.mint,
+            buy,
+            amount_in: spent,
+            amount_out: received,
+        });
+
+        Ok(())
+    }
+
+    // Make the vault a meta-vault over another vault whose shares are its
+    // underlying. Prices stay quoted in child shares; sync_meta_price
+    // composes them with the child's rate
+    pub fn link_child_vault(ctx: Context<LinkChildVault>) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Child shares are moved with plain transfer_checked, so they can't
+        // carry a transfer hook or be non-transferable
+        let child_mint = ctx.accounts.child_mint.to_account_info();
+        if child_mint.owner == &Token2022::id() {
+            let data = child_mint.try_borrow_data()?;
+            let extensions = StateWithExtensions::<Token2022Mint>::unpack(&data)?
+                .get_extension_types()?;
+            require!(
+                !extensions.contains(&ExtensionType::TransferHook)
+                    && !extensions.contains(&ExtensionType::NonTransferable),
+                ErrorCode::InvalidChildVault
+            );
+        }
+
+        ctx.accounts.vault.child_vault = ctx.accounts.child_vault.key();
+        Ok(())
+    }
+
+    // Permissionless: publish the meta-vault's share price in the child's
+    // underlying, valuing child shares at what they currently redeem for
+    pub fn sync_meta_price(ctx: Context<SyncMetaPrice>) -> Result<()> {
+        let child_vault = &ctx.accounts.child_vault;
+        let child_price = child_vault.redemption_value(
+            child_vault.unit(),
+            ctx.accounts.child_asset.price,
+            Clock::get()?.unix_timestamp,
+        );
+        let composed_price = child_vault.to_value(ctx.accounts.asset.price, child_price);
+
+        emit_event!(ctx.accounts, MetaPriceSynced {
+            vault: ctx.accounts.vault.key(),
+            child_vault: child_vault.key(),
+            price: ctx.accounts.asset.price,
+            child_price,
+            composed_price,
+        });
+
+        Ok(())
+    }
+
+    // Marks track the market, so they stay outside the timelock
+    pub fn update_price(ctx: Context<UpdateAssetParams>, price: u64) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(price > 0, ErrorCode::InvalidPrice);
+        require!(!ctx.accounts.asset.delisted, ErrorCode::AssetDelisted);
+
+        emit_event!(ctx.accounts, PriceUpdated {
+            asset: ctx.accounts.asset.key(),
+            old_price: ctx.accounts.asset.price,
+            new_price: price,
+        });
+        ctx.accounts.asset.price = price;
+
+        Ok(())
+    }
+
+    pub fn update_deposit_limit(
+        ctx: Context<UpdateAssetParams>,
+        deposit_limit: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        emit_event!(ctx.accounts, DepositLimitUpdated {
+            vault: vault.key(),
+            old_limit: vault.deposit_limit,
+            new_limit: deposit_limit,
+        });
+        vault.deposit_limit = deposit_limit;
+        Ok(())
+    }
+
+    pub fn set_per_user_cap(ctx: Context<UpdateAssetParams>, per_user_cap: u64) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        emit_event!(ctx.accounts, VaultLimitUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultLimit::PerUserCap,
+            old_value: ctx.accounts.vault.per_user_cap,
+            new_value: per_user_cap,
+        });
+        ctx.accounts.vault.per_user_cap = per_user_cap;
+        Ok(())
+    }
+
+    pub fn set_redeem_cooldown(
+        ctx: Context<UpdateAssetParams>,
+        redeem_cooldown_slots: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            redeem_cooldown_slots <= MAX_REDEEM_COOLDOWN_SLOTS,
+            ErrorCode::InvalidRedeemCooldown
+        );
+
+        ctx.accounts.vault.redeem_cooldown_slots = redeem_cooldown_slots;
+        Ok(())
+    }
+
+    pub fn set_redeem_epoch_duration(
+        ctx: Context<UpdateAssetParams>,
+        redeem_epoch_duration: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            (0..=MAX_REDEEM_EPOCH_DURATION).contains(&redeem_epoch_duration),
+            ErrorCode::InvalidRedeemEpochDuration
+        );
+
+        ctx.accounts.vault.redeem_epoch_duration = redeem_epoch_duration;
+        Ok(())
+    }
+
+    pub fn set_redeem_rate_limit(
+        ctx: Context<UpdateAssetParams>,
+        redeem_rate_limit: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        emit_event!(ctx.accounts, VaultLimitUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultLimit::RedeemRateLimit,
+            old_value: ctx.accounts.vault.redeem_rate_limit,
+            new_value: redeem_rate_limit,
+        });
+        ctx.accounts
+            .vault
+            .set_redeem_rate_limit(redeem_rate_limit, Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn set_deposit_inflow_cap(
+        ctx: Context<UpdateAssetParams>,
+        inflow_cap: u64,
+        inflow_window_slots: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            inflow_cap == 0 || inflow_window_slots > 0,
+            ErrorCode::InvalidInflowWindow
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        emit_event!(ctx.accounts, VaultLimitUpdated {
+            vault: vault.key(),
+            kind: VaultLimit::InflowCap,
+            old_value: vault.inflow_cap,
+            new_value: inflow_cap,
+        });
+        vault.inflow_cap = inflow_cap;
+        vault.inflow_window_slots = inflow_window_slots;
+        Ok(())
+    }
+
+    pub fn set_min_deposit(ctx: Context<UpdateAssetParams>, min_deposit: u64) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        emit_event!(ctx.accounts, VaultLimitUpdated {
+            vault: ctx.accounts.vault.key(),
+            kind: VaultLimit::MinDeposit,
+            old_value: ctx.accounts.vault.min_deposit,
+            new_value: min_deposit,
+        });
+        ctx.accounts.vault.min_deposit = min_deposit;
+        Ok(())
+    }
+
+    pub fn set_dust_threshold(
+        ctx: Context<UpdateAssetParams>,
+        dust_threshold: u64,
+        sweep_dust: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.dust_threshold = dust_threshold;
+        vault.sweep_dust = sweep_dust;
+        Ok(())
+    }
+
+    pub fn set_allowlist_enabled(ctx: Context<UpdateAssetParams>, enabled: bool) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.vault.allowlist_enabled = enabled;
+        Ok(())
+    }
+
+    pub fn set_allowlist_root(ctx: Context<UpdateAssetParams>, root: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.vault.allowlist_root = root;
+        Ok(())
+    }
+
+    pub fn set_credential_mint(
+        ctx: Context<UpdateAssetParams>,
+        credential_mint: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.vault.credential_mint = credential_mint;
+        Ok(())
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, wallet: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let entry = &mut ctx.accounts.allowlist_entry;
+        entry.vault = ctx.accounts.vault.key();
+        entry.wallet = wallet;
+        Ok(())
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    // Share mints created with the Token-2022 transfer hook extension
+    // pointing at this program need the extra accounts the hook reads
+    pub fn init_transfer_hook(ctx: Context<InitTransferHook>) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Indices 0-4 are source, mint, destination, owner and this list;
+        // token account owners sit at byte 32 of the account data
+        let source_owner = Seed::AccountData {
+            account_index: 0,
+            data_index: 32,
+            length: 32,
+        };
+        let destination_owner = Seed::AccountData {
+            account_index: 2,
+            data_index: 32,
+            length: 32,
+        };
+        // The asset and vault are keyed by the underlying mint and vault id,
+        // neither of which the hook can read off the share mint, so they're
+        // listed by address
+        let metas = [
+            ExtraAccountMeta::new_with_pubkey(&ctx.accounts.asset.key(), false, false)?,
+            ExtraAccountMeta::new_with_pubkey(&ctx.accounts.asset.vault, false, false)?,
+            ExtraAccountMeta::new_with_seeds(
+                &[Seed::Literal { bytes: b"blacklist".to_vec() }, source_owner],
+                false,
+                false,
+            )?,
+            ExtraAccountMeta::new_with_seeds(
+                &[Seed::Literal { bytes: b"blacklist".to_vec() }, destination_owner.clone()],
+                false,
+                false,
+            )?,
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal { bytes: b"allowlist".to_vec() },
+                    Seed::AccountKey { index: 6 },
+                    destination_owner,
+                ],
+                false,
+                false,
+            )?,
+        ];
+
+        let mut data = ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
+        Ok(())
+    }
+
+    // Called by Token-2022 on every share transfer, so secondary transfers
+    // face the same blacklist and allowlist as deposits
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        let source_info = ctx.accounts.source.to_account_info();
+        let source_data = source_info.try_borrow_data()?;
+        let source = StateWithExtensions::<Token2022Account>::unpack(&source_data)?;
+        require!(
+            bool::from(source.get_extension::<TransferHookAccount>()?.transferring),
+            ErrorCode::NotTransferring
+        );
+
+        require!(
+            ctx.accounts.source_blacklist_entry.data_is_empty()
+                && ctx.accounts.destination_blacklist_entry.data_is_empty(),
+            ErrorCode::Blacklisted
+        );
+
+        // Shares moving into the vault's own PDAs (redemption queue,
+        // staking, vote escrow) don't need an allowlist entry; merkle
+        // proofs can't ride along a transfer, so other holders do
+        let vault = &ctx.accounts.vault;
+        if vault.allowlist_enabled
+            && ctx.accounts.destination_allowlist_entry.data_is_empty()
+        {
+            let mint = ctx.accounts.mint.key();
+            let vault_key = vault.key();
+            let destination_owner = ctx.accounts.destination.owner;
+            let program_owned = [
+                &[b"vault_signer".as_ref(), mint.as_ref(), &[vault.signer_version]][..],
+                &[b"reward_pool".as_ref(), vault_key.as_ref()][..],
+                &[b"ve_authority".as_ref(), vault_key.as_ref()][..],
+            ]
+            .iter()
+            .any(|seeds| Pubkey::find_program_address(seeds, &crate::ID).0 == destination_owner);
+            require!(program_owned, ErrorCode::NotAllowlisted);
+        }
+
+        Ok(())
+    }
+
+    pub fn delist_asset(
+        ctx: Context<UpdateAssetParams>,
+        settlement_price: u64,
+        window: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+        require!(
+            settlement_price > 0 && window > 0,
+            ErrorCode::InvalidSettlement
+        );
+
+        ctx.accounts
+            .asset
+            .delist(settlement_price, window, Clock::get()?.unix_timestamp)
+    }
+
+    pub fn renounce_asset_authority(ctx: Context<UpdateAsset>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        emit_event!(ctx.accounts, AssetAuthorityTransferred {
+            asset: asset.key(),
+            old_authority: asset.authority,
+            new_authority: Pubkey::default(),
+        });
+        asset.authority = Pubkey::default();
+        asset.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn close_asset(ctx: Context<CloseAsset>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &ctx.accounts.vault;
+        require!(
+            asset.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+        require!(
+            vault.total_assets == 0
+                && vault.total_usdc == 0
+                && vault.fees_outstanding == 0
+                && vault.fee_shares_outstanding == 0
+                && vault.admin_debt == 0
+                && vault.strategy_debt == 0
+                && vault.strategy_gain == 0
+                && vault.queued_redeem_shares == 0
+                && vault.claimable_usdc == 0,
+            ErrorCode::VaultNotEmpty
+        );
+
+        // Close the vault token account; the asset's other PDAs close on exit
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let close_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.vault_usdc_account.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::close_account(close_ctx)?;
+
+        Ok(())
+    }
+
+    pub fn pause_vault(ctx: Context<SetVaultPause>, flags: u8) -> Result<()> {
+        let admin = &ctx.accounts.admin;
+        let signer = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.asset.authority == signer
+                || admin.guardian == signer
+                || admin.has_role(signer, Role::Pauser, ctx.accounts.role_grant.as_deref()),
+            ErrorCode::Unauthorized
+        );
+        require!(flags & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        // Holders of a vault being wound down must always be able to exit
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            !vault.shutdown || flags & PAUSE_REDEMPTIONS == 0,
+            ErrorCode::VaultShutdown
+        );
+
+        emit_event!(ctx.accounts, VaultPauseUpdated {
+            vault: vault.key(),
+            old_flags: vault.pause_flags,
+            new_flags: vault.pause_flags | flags,
+        });
+        vault.pause_flags |= flags;
+        Ok(())
+    }
+
+    pub fn unpause_vault(ctx: Context<SetVaultPause>, flags: u8) -> Result<()> {
+        let signer = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.asset.authority == signer
+                || ctx.accounts.admin.has_role(
+                    signer,
+                    Role::VaultManager,
+                    ctx.accounts.role_grant.as_deref()
+                ),
+            ErrorCode::Unauthorized
+        );
+        require!(flags & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        emit_event!(ctx.accounts, VaultPauseUpdated {
+            vault: ctx.accounts.vault.key(),
+            old_flags: ctx.accounts.vault.pause_flags,
+            new_flags: ctx.accounts.vault.pause_flags & !flags,
+        });
+        ctx.accounts.vault.pause_flags &= !flags;
+        Ok(())
+    }
+
+    pub fn shutdown_vault(ctx: Context<UpdateVault>) -> Result<()> {
+        let signer = ctx.accounts.authority.key();
+        require!(
+            ctx.accounts.asset.authority == signer
+                || ctx.accounts.admin.has_role(
+                    signer,
+                    Role::SuperAdmin,
+                    ctx.accounts.role_grant.as_deref()
+                ),
+            ErrorCode::Unauthorized
+        );
+
+        // Irreversible: deposits and admin withdrawals stay closed for good,
+        // redemptions are reopened so holders can exit
+        let vault = &mut ctx.accounts.vault;
+        require!(!vault.shutdown, ErrorCode::VaultShutdown);
+        vault.shutdown = true;
+        vault.pause_flags = PAUSE_DEPOSITS;
+        Ok(())
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        require!(!ctx.accounts.config.global_pause, ErrorCode::ProtocolPaused);
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::FeeCollector,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let amount = vault.fees_outstanding;
+        let fee_shares = vault.fee_shares_outstanding;
+        require!(amount > 0 || fee_shares > 0, ErrorCode::NoFeesToCollect);
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // Transfer accrued USDC fees from vault to fee recipient
+        if amount > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.usdc_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_usdc_account.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: ctx.accounts.fee_recipient_usdc_account.to_account_info(),
+                    authority: ctx.accounts.vault_signer.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::transfer_checked(
+                transfer_ctx,
+                amount,
+                ctx.accounts.usdc_mint.decimals,
+            )?;
+            vault.fees_outstanding = 0;
+        }
+
+        // Mint accrued fee shares to fee recipient
+        if fee_shares > 0 {
+            let (Some(asset_mint), Some(fee_recipient_asset_account)) = (
+                ctx.accounts.asset_mint.as_ref(),
+                ctx.accounts.fee_recipient_asset_account.as_ref(),
+            ) else {
+                return err!(ErrorCode::MissingFeeShareAccounts);
+            };
+
+            let mint_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: asset_mint.to_account_info(),
+                    to: fee_recipient_asset_account.to_account_info(),
+                    authority: ctx.accounts.vault_signer.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::mint_to(mint_ctx, fee_shares)?;
+            vault.total_assets = vault.total_assets.checked_add(fee_shares).unwrap();
+            vault.fee_shares_outstanding = 0;
+        }
+
+        Ok(())
+    }
+
+    // Permissionless: reconcile USDC sent straight to the vault account.
+    // Anything above tracked liquidity, fees and settled redemptions is a
+    // donation, either credited to holders or swept to the treasury
+    pub fn sync(ctx: Context<SyncVault>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let expected = vault
+            .total_usdc
+            .checked_add(vault.fees_outstanding)
+            .unwrap()
+            .checked_add(vault.claimable_usdc)
+            .unwrap();
+        let balance = ctx.accounts.vault_usdc_account.amount;
+        let surplus = balance.saturating_sub(expected);
+        require!(surplus > 0, ErrorCode::NothingToSync);
+
+        emit_event!(ctx.accounts, DonationDetected {
+            vault: vault.key(),
+            expected,
+            balance,
+            surplus,
+            swept: vault.sweep_donations,
+        });
+
+        if vault.sweep_donations {
+            let treasury_usdc_account = ctx
+                .accounts
+                .treasury_usdc_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingTreasuryAccount)?;
+
+            let seeds = &[
+                b"vault_signer".as_ref(),
+                asset.mint.as_ref(),
+                &[vault.signer_version],
+                &[vault.signer_bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_usdc_account.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                    to: treasury_usdc_account.to_account_info(),
+                    authority: ctx.accounts.vault_signer.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::transfer_checked(
+                transfer_ctx,
+                surplus,
+                ctx.accounts.usdc_mint.decimals,
+            )?;
+        } else {
+            // Donations unlock into share pricing like harvested profit
+            vault.total_usdc = vault.total_usdc.checked_add(surplus).unwrap();
+            vault.lock_profit(surplus, Clock::get()?.unix_timestamp);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_donation_policy(ctx: Context<UpdateVault>, sweep_donations: bool) -> Result<()> {
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::VaultManager,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.config.timelock_delay == 0,
+            ErrorCode::TimelockRequired
+        );
+
+        ctx.accounts.vault.sweep_donations = sweep_donations;
+        Ok(())
+    }
+
+    // Move tokens sent to a vault account by mistake out to the treasury.
+    // The vault's USDC, its own shares and strategy positions are never
+    // sweepable
+    pub fn sweep_tokens(ctx: Context<SweepTokens>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.admin.has_role(
+                ctx.accounts.authority.key(),
+                Role::SuperAdmin,
+                ctx.accounts.role_grant.as_deref()
+            ),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            vault.signer_version == ctx.accounts.admin.signer_version,
+            ErrorCode::VaultSignerOutdated
+        );
+
+        let source = &ctx.accounts.source;
+        require!(
+            source.mint != ctx.accounts.vault_usdc_account.mint
+                && source.mint != asset.mint
+                && ctx.accounts.quote_entry.data_is_empty()
+                && ctx.accounts.strategy_account.data_is_empty()
+                && source.key() != basket_account(vault.key(), source.mint),
+            ErrorCode::CannotSweepVaultToken
+        );
+        let amount = source.amount;
+        require!(amount > 0, ErrorCode::NothingToSweep);
+
+        let seeds = &[
+            b"vault_signer".as_ref(),
+            asset.mint.as_ref(),
+            &[vault.signer_version],
+            &[vault.signer_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: source.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.vault_signer.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit_event!(ctx.accounts, TokensSwept {
+            vault: vault.key(),
+            mint: source.mint,
+            amount,
+            treasury: ctx.accounts.config.treasury,
+        });
+
+        Ok(())
+    }
+}
+
+// Allocate and initialize a share mint with the vault signer as mint and
+// freeze authority, plus whichever Token-2022 extensions `share_config` asks
+// for. `mint_seeds` sign for a PDA mint and are empty for a keypair
+fn create_share_mint<'info>(
+    mint: &AccountInfo<'info>,
+    vault_signer: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    share_config: &ShareMintConfig,
+    mint_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let token_2022 = token_program.key() == Token2022::id();
+    require!(
+        token_2022
+            || !(share_config.confidential
+                || share_config.transfer_hook
+                || share_config.non_transferable),
+        ErrorCode::Token2022Required
+    );
+    require!(
+        !(share_config.non_transferable
+            && (share_config.confidential || share_config.transfer_hook)),
+        ErrorCode::InvalidShareConfig
+    );
+    require!(
+        SHARE_DECIMALS.contains(&share_config.decimals),
+        ErrorCode::InvalidShareDecimals
+    );
+    let mut extensions = Vec::new();
+    if token_2022 {
+        extensions.push(ExtensionType::MetadataPointer);
+    }
+    if share_config.confidential {
+        extensions.push(ExtensionType::ConfidentialTransferMint);
+    }
+    if share_config.transfer_hook {
+        extensions.push(ExtensionType::TransferHook);
+    }
+    if share_config.non_transferable {
+        extensions.push(ExtensionType::NonTransferable);
+    }
+
+    let space = ExtensionType::try_calculate_account_len::<Token2022Mint>(&extensions)?;
+    let create_ctx = CpiContext::new_with_signer(
+        system_program.clone(),
+        system_program::CreateAccount {
+            from: payer.clone(),
+            to: mint.clone(),
+        },
+        mint_seeds,
+    );
+    system_program::create_account(
+        create_ctx,
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        token_program.key,
+    )?;
+
+    // Extensions must be initialized before the mint itself
+    if token_2022 {
+        let pointer_ctx = CpiContext::new(
+            token_program.clone(),
+            token_interface::MetadataPointerInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            },
+        );
+        token_interface::metadata_pointer_initialize(
+            pointer_ctx,
+            Some(vault_signer),
+            Some(mint.key()),
+        )?;
+    }
+    if share_config.confidential {
+        let ix = confidential_transfer::instruction::initialize_mint(
+            &Token2022::id(),
+            &mint.key(),
+            Some(vault_signer),
+            share_config.auto_approve,
+            share_config.auditor.map(PodElGamalPubkey::from),
+        )?;
+        invoke(&ix, &[mint.clone(), token_program.clone()])?;
+    }
+    if share_config.transfer_hook {
+        let hook_ctx = CpiContext::new(
+            token_program.clone(),
+            token_interface::TransferHookInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            },
+        );
+        token_interface::transfer_hook_initialize(hook_ctx, Some(vault_signer), Some(crate::ID))?;
+    }
+    if share_config.non_transferable {
+        let soulbound_ctx = CpiContext::new(
+            token_program.clone(),
+            token_interface::NonTransferableMintInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            },
+        );
+        token_interface::non_transferable_mint_initialize(soulbound_ctx)?;
+    }
+
+    let init_ctx = CpiContext::new(
+        token_program.clone(),
+        token_interface::InitializeMint2 { mint: mint.clone() },
+    );
+    token_interface::initialize_mint2(
+        init_ctx,
+        share_config.decimals,
+        &vault_signer,
+        Some(&vault_signer),
+    )?;
+
+    Ok(())
+}
+
+// Allocate a wSOL token account at a program PDA, owned by `owner`, who
+// pays its rent plus `amount` lamports that become the wrapped balance
+fn open_wsol_account<'info>(
+    account: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    native_mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+    account_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let create_ctx = CpiContext::new_with_signer(
+        system_program.clone(),
+        system_program::CreateAccount {
+            from: owner.clone(),
+            to: account.clone(),
+        },
+        account_seeds,
+    );
+    system_program::create_account(
+        create_ctx,
+        Rent::get()?
+            .minimum_balance(TokenAccount::LEN)
+            .checked_add(amount)
+            .unwrap(),
+        TokenAccount::LEN as u64,
+        token_program.key,
+    )?;
+
+    let init_ctx = CpiContext::new(
+        token_program.clone(),
+        token_interface::InitializeAccount3 {
+            account: account.clone(),
+            mint: native_mint.clone(),
+            authority: owner.clone(),
+        },
+    );
+    token_interface::initialize_account3(init_ctx)
+}
+
+// Tickers are claimed case-insensitively and without surrounding spaces
+fn normalize_ticker(ticker: &str) -> String {
+    ticker.trim().to_ascii_uppercase()
+}
+
+// Escrow the listing bond (in lamports) in the asset's bond PDA, on top of
+// its rent
+fn post_bond<'info>(
+    bond: &mut Account<'info, Bond>,
+    asset: Pubkey,
+    config: &Config,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if config.listing_bond > 0 {
+        let bond_ctx = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: bond.to_account_info(),
+            },
+        );
+        system_program::transfer(bond_ctx, config.listing_bond)?;
+    }
+
+    bond.asset = asset;
+    bond.creator = payer.key();
+    bond.amount = config.listing_bond;
+    Ok(())
+}
+
+// Charge the protocol creation fee (in lamports) to the creator
+fn charge_creation_fee<'info>(
+    config: &Config,
+    payer: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if config.creation_fee > 0 {
+        let fee_ctx = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: treasury.clone(),
+            },
+        );
+        system_program::transfer(fee_ctx, config.creation_fee)?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_asset(
+    asset: &mut Account<Asset>,
+    vault: &mut Account<Vault>,
+    params: AssetParams,
+    mint: Pubkey,
+    authority: Pubkey,
+    signer_version: u8,
+    signer_bump: u8,
+    underlying_mint: &InterfaceAccount<token_interface::Mint>,
+) -> Result<()> {
+    require!(params.name.len() <= 50, ErrorCode::NameTooLong);
+    require!(params.ticker.len() <= 10, ErrorCode::TickerTooLong);
+    let ticker = normalize_ticker(&params.ticker);
+    require!(
+        !ticker.is_empty()
+            && ticker
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-'),
+        ErrorCode::InvalidTicker
+    );
+
+    asset.name = params.name;
+    asset.ticker = params.ticker;
+    asset.price = params.price;
+    asset.mint = mint;
+    asset.vault = vault.key();
+    asset.underlying_mint = underlying_mint.key();
+    asset.vault_id = params.vault_id;
+    asset.authority = authority;
+    asset.category = params.category;
+    asset.tags = params.tags;
+
+    vault.deposit_limit = params.deposit_limit;
+    vault.signer_version = signer_version;
+    vault.signer_bump = signer_bump;
+    vault.fee_recipient = authority;
+    vault.withdraw_cap_bps = BPS_DENOMINATOR as u16;
+    vault.min_collateral_bps = BPS_DENOMINATOR as u16;
+    vault.underlying_mint = underlying_mint.key();
+    vault.underlying_decimals = underlying_mint.decimals;
+    vault.share_decimals = params.share_config.decimals;
+
+    Ok(())
+}
+
+// Same encoding as Anchor's emit_cpi!, which needs `ctx` in scope and so
+// can't be used from the shared deposit and redeem paths
+#[cfg(feature = "cpi-events")]
+fn emit_self_cpi<E: anchor_lang::Event>(event_authority: &AccountInfo, event: &E) -> Result<()> {
+    let (_, bump) = Pubkey::find_program_address(&[b"__event_authority"], &crate::ID);
+    let data = anchor_lang::event::EVENT_IX_TAG_LE
+        .iter()
+        .copied()
+        .chain(event.data())
+        .collect();
+    let ix = Instruction {
+        program_id: crate::ID,
+        accounts: vec![AccountMeta::new_readonly(event_authority.key(), true)],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        std::slice::from_ref(event_authority),
+        &[&[b"__event_authority", &[bump]]],
+    )?;
+    Ok(())
+}
+
+// CPI into a strategy adapter following the Anchor sighash convention:
+// `<name>(amount)` with the vault signer and vault USDC account first and any
+// adapter-specific accounts after them. `amount` is in USDC
+fn invoke_adapter<'info>(
+    adapter_program: &AccountInfo<'info>,
+    name: &str,
+    amount: u64,
+    vault_signer: &AccountInfo<'info>,
+    vault_usdc_account: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(vault_signer.key(), true),
+        AccountMeta::new(vault_usdc_account.key(), false),
+    ];
+    let mut infos = vec![
+        adapter_program.clone(),
+        vault_signer.clone(),
+        vault_usdc_account.clone(),
+    ];
+    for account in remaining_accounts {
+        accounts.push(if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        });
+        infos.push(account.clone());
+    }
+
+    let ix = Instruction {
+        program_id: adapter_program.key(),
+        accounts,
+        data,
+    };
+    invoke_signed(&ix, &infos, signer_seeds)?;
+    Ok(())
+}
+
+// Tip the crank caller out of accrued USDC fees: a flat amount plus bps of
+// the value processed, capped at what the vault has collected
+#[allow(clippy::too_many_arguments)]
+fn pay_keeper_tip<'info>(
+    vault: &mut Vault,
+    processed: u64,
+    keeper_usdc_account: Option<&InterfaceAccount<'info, token_interface::TokenAccount>>,
+    vault_usdc_account: &InterfaceAccount<'info, token_interface::TokenAccount>,
+    usdc_mint: &InterfaceAccount<'info, token_interface::Mint>,
+    vault_signer: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let Some(keeper_usdc_account) = keeper_usdc_account else {
+        return Ok(());
+    };
+    let tip = vault
+        .keeper_tip
+        .checked_add(bps_of(processed, vault.keeper_tip_bps))
+        .unwrap()
+        .min(vault.fees_outstanding);
+    if tip == 0 {
+        return Ok(());
+    }
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        TransferChecked {
+            from: vault_usdc_account.to_account_info(),
+            mint: usdc_mint.to_account_info(),
+            to: keeper_usdc_account.to_account_info(),
+            authority: vault_signer.clone(),
+        },
+        signer_seeds,
+    );
+    token_interface::transfer_checked(transfer_ctx, tip, usdc_mint.decimals)?;
+    vault.fees_outstanding = vault.fees_outstanding.checked_sub(tip).unwrap();
+
+    Ok(())
+}
+
+// transfer_checked for share mints. Token-2022 mints with a transfer hook
+// need the hook's extra accounts, which the caller passes as remaining
+// accounts and Token-2022 resolves from the mint's extra account meta list
+#[allow(clippy::too_many_arguments)]
+fn transfer_shares<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, token_interface::Mint>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    onchain::invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.to_account_info(),
+        to.clone(),
+        authority.clone(),
+        remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+// USDC value of everything the adapter holds for the vault, read back from
+// the adapter's return data
+fn adapter_total_value<'info>(
+    adapter_program: &AccountInfo<'info>,
+    vault_signer: &AccountInfo<'info>,
+    vault_usdc_account: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    invoke_adapter(
+        adapter_program,
+        "total_value",
+        0,
+        vault_signer,
+        vault_usdc_account,
+        remaining_accounts,
+        signer_seeds,
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == adapter_program.key() => Ok(u64::from_le_bytes(
+            data.get(..8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(ErrorCode::InvalidAdapterValue)?,
+        )),
+        _ => err!(ErrorCode::InvalidAdapterValue),
+    }
+}
+
+// Deposit at the asset price, shared by deposit, deposit_with_memo,
+// deposit_sol and deposit_with_swap
+fn deposit_underlying<'info>(
+    accounts: &mut Deposit<'info>,
+    source: AccountInfo<'info>,
+    amount: u64,
+    proof: &[[u8; 32]],
+    memo: Option<String>,
+) -> Result<()> {
+    let asset = &accounts.asset;
+    let vault = &mut accounts.vault;
+    require!(!accounts.config.global_pause, ErrorCode::ProtocolPaused);
+    require!(!vault.shutdown, ErrorCode::VaultShutdown);
+    require!(!asset.delisted, ErrorCode::AssetDelisted);
+    require!(
+        vault.pause_flags & PAUSE_DEPOSITS == 0,
+        ErrorCode::DepositsPaused
+    );
+    require!(
+        vault.signer_version == accounts.admin.signer_version,
+        ErrorCode::VaultSignerOutdated
+    );
+    require!(amount >= vault.min_deposit, ErrorCode::DepositTooSmall);
+    vault.check_basket_fresh(Clock::get()?.unix_timestamp)?;
+    require!(
+        accounts.blacklist_entry.data_is_empty(),
+        ErrorCode::Blacklisted
+    );
+    if vault.allowlist_enabled {
+        require!(
+            accounts.allowlist_entry.is_some()
+                || vault.allowlist_contains(accounts.user.key(), proof),
+            ErrorCode::NotAllowlisted
+        );
+    }
+
+    // Investor verification: hold a credential token issued under the
+    // vault's credential mint
+    if vault.credential_mint != Pubkey::default() {
+        let credential = accounts
+            .credential
+            .as_ref()
+            .ok_or(ErrorCode::MissingCredential)?;
+        require!(
+            credential.mint == vault.credential_mint
+                && credential.owner == accounts.user.key()
+                && credential.amount > 0,
+            ErrorCode::MissingCredential
+        );
+    }
+
+    // Bootstrap a fresh vault with enough liquidity to avoid precision
+    // and manipulation issues at near-zero supply
+    if vault.total_assets == 0 && vault.fee_shares_outstanding == 0 {
+        require!(
+            amount >= accounts.config.min_initial_deposit,
+            ErrorCode::InitialDepositTooSmall
+        );
+    }
+    
+    // Deposit fee is either held aside in USDC or left in the vault as
+    // backing for fee shares; a share class charges its own rate
+    let deposit_fee_bps = accounts
+        .share_class
+        .as_ref()
+        .map_or(vault.deposit_fee_bps, |class| class.deposit_fee_bps);
+    let fee = bps_of(amount, deposit_fee_bps);
+
+    // Referrer takes a slice of the deposit fee straight from the user
+    let referral_fee = match &accounts.referrer {
+        Some(referrer) => {
+            require!(
+                referrer.wallet != accounts.user.key(),
+                ErrorCode::SelfReferral
+            );
+            bps_of(fee, vault.referral_fee_bps)
+        }
+        None => 0,
+    };
+    let vault_fee = fee.checked_sub(referral_fee).unwrap();
+
+    // Only what the vault actually receives after any Token-2022
+    // transfer fee backs the minted shares
+    let withheld = transfer_fee(
+        &accounts.usdc_mint,
+        amount.checked_sub(referral_fee).unwrap(),
+    )?;
+    let net_amount = amount.checked_sub(fee).unwrap().checked_sub(withheld).unwrap();
+    let backing = match vault.fee_mode {
+        FeeMode::Assets => net_amount,
+        FeeMode::Shares => net_amount.checked_add(vault_fee).unwrap(),
+    };
+
+    // Per-wallet cap on cumulative deposits, tracked on the user's position
+    let position = &mut accounts.position;
+    position.deposited = position.deposited.checked_add(amount).unwrap();
+    position.last_deposit_slot = Clock::get()?.slot;
+    position.record_activity(Clock::get()?.slot);
+    require!(
+        vault.per_user_cap == 0 || position.deposited <= vault.per_user_cap,
+        ErrorCode::PerUserCapExceeded
+    );
+
+    vault.record_inflow(amount, Clock::get()?.slot)?;
+
+    // Check if current deposit plus existing stablecoins would exceed limit
+    let new_total = vault.total_usdc.checked_add(backing).unwrap();
+    require!(
+        new_total.checked_add(vault.quote_backing).unwrap() <= vault.deposit_limit,
+        ErrorCode::DepositLimitExceeded
+    );
+    
+    // Calculate asset tokens to mint based on USDC amount and price
+    let asset_amount = vault.to_shares(net_amount, asset.price);
+    require!(asset_amount > 0, ErrorCode::ZeroShares);
+
+    // Transfer USDC from user to vault
+    let transfer_ctx = CpiContext::new(
+        accounts.usdc_token_program.to_account_info(),
+        TransferChecked {
+            from: source.clone(),
+            mint: accounts.usdc_mint.to_account_info(),
+            to: accounts.vault_usdc_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        },
+    );
+    token_interface::transfer_checked(
+        transfer_ctx,
+        amount.checked_sub(referral_fee).unwrap(),
+        accounts.usdc_mint.decimals,
+    )?;
+
+    // Pay the referral fee and record the referred volume
+    if let Some(referrer) = accounts.referrer.as_mut() {
+        if referral_fee > 0 {
+            let referrer_usdc_account = accounts
+                .referrer_usdc_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingReferrerAccount)?;
+            require!(
+                referrer_usdc_account.owner == referrer.wallet,
+                ErrorCode::MissingReferrerAccount
+            );
+
+            let referral_ctx = CpiContext::new(
+                accounts.usdc_token_program.to_account_info(),
+                TransferChecked {
+                    from: source.clone(),
+                    mint: accounts.usdc_mint.to_account_info(),
+                    to: referrer_usdc_account.to_account_info(),
+                    authority: accounts.user.to_account_info(),
+                },
+            );
+            token_interface::transfer_checked(
+                referral_ctx,
+                referral_fee,
+                accounts.usdc_mint.decimals,
+            )?;
+        }
+
+        referrer.lifetime_volume = referrer.lifetime_volume.checked_add(amount).unwrap();
+        referrer.lifetime_rewards = referrer.lifetime_rewards.checked_add(referral_fee).unwrap();
+    }
+
+    // Mint asset tokens to user
+    let seeds = &[
+        b"vault_signer".as_ref(),
+        asset.mint.as_ref(),
+        &[vault.signer_version],
+        &[vault.signer_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mint_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        token_interface::MintTo {
+            mint: accounts.asset_mint.to_account_info(),
+            to: accounts.user_asset_account.to_account_info(),
+            authority: accounts.vault_signer.to_account_info(),
+        },
+        signer,
+    );
+    token_interface::mint_to(mint_ctx, asset_amount)?;
+    if let Some(class) = accounts.share_class.as_mut() {
+        class.supply = class.supply.checked_add(asset_amount).unwrap();
+    }
+
+    // Time-weighted balance follows the user's share ATA
+    if accounts.user_asset_account.key()
+        == get_associated_token_address_with_program_id(
+            &accounts.user.key(),
+            &asset.mint,
+            &accounts.token_program.key(),
+        )
+    {
+        accounts.user_asset_account.reload()?;
+        accounts.position.observe_balance(
+            accounts.user_asset_account.amount,
+            Clock::get()?.unix_timestamp,
+        );
+    }
+
+    // Update vault state
+    vault.total_usdc = new_total;
+    vault.total_assets = vault.total_assets.checked_add(asset_amount).unwrap();
+    accounts.stats.record_deposit(amount, vault.total_backing());
+
+    emit_event!(accounts, DepositEvent {
+        user: accounts.user.key(),
+        asset: asset.key(),
+        usdc_in: amount,
+        shares_out: asset_amount,
+        price: asset.price,
+        memo,
+    });
+    match vault.fee_mode {
+        FeeMode::Assets => {
+            vault.fees_outstanding = vault.fees_outstanding.checked_add(vault_fee).unwrap();
+        }
+        FeeMode::Shares => {
+            let fee_shares = vault.to_shares(vault_fee, asset.price);
+            vault.fee_shares_outstanding =
+                vault.fee_shares_outstanding.checked_add(fee_shares).unwrap();
+        }
+    }
+
+    Ok(())
+}
+
+// Fails if any instruction in the transaction deposits into this program.
+// `skip` is the caller's own index when it is itself a deposit (swap_assets)
+fn check_no_deposit_in_transaction(instructions: &AccountInfo, skip: Option<usize>) -> Result<()> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        require!(
+            Some(index) == skip
+                || ix.program_id != crate::ID
+                || !(ix.data.starts_with(instruction::Deposit::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::DepositWithMemo::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::DepositSol::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::DepositWithSwap::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::DepositQuote::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::SwapAssets::DISCRIMINATOR)
+                    || ix.data.starts_with(instruction::BatchDeposit::DISCRIMINATOR)),
+            ErrorCode::SameSlotRedeem
+        );
+        index += 1;
+    }
+    Ok(())
+}
+
+// Instant redemption at the asset price, shared by redeem and redeem_all
+fn redeem_shares<'info>(
+    accounts: &mut Redeem<'info>,
+    destination: AccountInfo<'info>,
+    amount: u64,
+    memo: Option<String>,
+) -> Result<()> {
+    let amount = accounts
+        .vault
+        .dust_adjusted(amount, accounts.user_asset_account.amount)?;
+    let asset = &accounts.asset;
+    let vault = &mut accounts.vault;
+    require!(!accounts.config.global_pause, ErrorCode::ProtocolPaused);
+    require!(
+        vault.pause_flags & PAUSE_REDEMPTIONS == 0,
+        ErrorCode::RedemptionsPaused
+    );
+    require!(
+        !asset.delisted || Clock::get()?.unix_timestamp <= asset.settlement_deadline,
+        ErrorCode::SettlementWindowClosed
+    );
+    require!(
+        accounts.blacklist_entry.data_is_empty(),
+        ErrorCode::Blacklisted
+    );
+    require!(
+        vault.signer_version == accounts.admin.signer_version,
+        ErrorCode::VaultSignerOutdated
+    );
+    vault.check_basket_fresh(Clock::get()?.unix_timestamp)?;
+
+    // Close the deposit-then-redeem loop around price updates: no redeem
+    // in a transaction that deposits, or in the slot of the last deposit
+    check_no_deposit_in_transaction(&accounts.instructions, None)?;
+    let position = &accounts.position;
+    require!(
+        position.last_deposit_slot != Clock::get()?.slot,
+        ErrorCode::SameSlotRedeem
+    );
+
+    // Shares must be held for the cooldown before they can be redeemed
+    if vault.redeem_cooldown_slots > 0 {
+        let unlock_slot = position
+            .last_deposit_slot
+            .checked_add(vault.redeem_cooldown_slots)
+            .unwrap();
+        require!(
+            Clock::get()?.slot >= unlock_slot,
+            ErrorCode::RedeemCooldownActive
+        );
+    }
+
+    // Share classes hold each deposit for their own lockup and charge
+    // their own redeem fee
+    let mut redeem_fee_bps = vault.redeem_fee_bps;
+    if let Some(class) = accounts.share_class.as_ref() {
+        if class.lockup_slots > 0 {
+            let unlock_slot = position
+                .last_deposit_slot
+                .checked_add(class.lockup_slots)
+                .unwrap();
+            require!(
+                Clock::get()?.slot >= unlock_slot,
+                ErrorCode::ShareClassLocked
+            );
+        }
+        redeem_fee_bps = class.redeem_fee_bps;
+    }
+
+    // Calculate USDC amount based on asset tokens and price
+    let usdc_amount = vault.redemption_value(amount, asset.price, Clock::get()?.unix_timestamp);
+    let fee = bps_of(usdc_amount, redeem_fee_bps);
+    let payout = usdc_amount.checked_sub(fee).unwrap();
+    vault.consume_redeem_capacity(payout, Clock::get()?.unix_timestamp)?;
+
+    // Burn asset tokens
+    let burn_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        token_interface::Burn {
+            mint: accounts.asset_mint.to_account_info(),
+            from: accounts.user_asset_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        },
+    );
+    token_interface::burn(burn_ctx, amount)?;
+    if let Some(class) = accounts.share_class.as_mut() {
+        class.supply = class.supply.checked_sub(amount).unwrap();
+    }
+
+    // Transfer USDC from vault to user
+    let seeds = &[
+        b"vault_signer".as_ref(),
+        asset.mint.as_ref(),
+        &[vault.signer_version],
+        &[vault.signer_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        accounts.usdc_token_program.to_account_info(),
+        TransferChecked {
+            from: accounts.vault_usdc_account.to_account_info(),
+            mint: accounts.usdc_mint.to_account_info(),
+            to: destination,
+            authority: accounts.vault_signer.to_account_info(),
+        },
+        signer,
+    );
+    token_interface::transfer_checked(transfer_ctx, payout, accounts.usdc_mint.decimals)?;
+
+    // The user is credited with the payout net of any transfer fee
+    let received = payout
+        .checked_sub(transfer_fee(&accounts.usdc_mint, payout)?)
+        .unwrap();
+    let position = &mut accounts.position;
+    position.redeemed = position.redeemed.checked_add(received).unwrap();
+    position.record_activity(Clock::get()?.slot);
+    if accounts.user_asset_account.key()
+        == get_associated_token_address_with_program_id(
+            &accounts.user.key(),
+            &asset.mint,
+            &accounts.token_program.key(),
+        )
+    {
+        accounts.user_asset_account.reload()?;
+        position.observe_balance(
+            accounts.user_asset_account.amount,
+            Clock::get()?.unix_timestamp,
+        );
+    }
+
+    // Update vault state
+    vault.book_redemption(amount, usdc_amount, fee, redeem_fee_bps);
+    accounts.stats.record_redeem(usdc_amount);
+
+    emit_event!(accounts, RedeemEvent {
+        user: accounts.user.key(),
+        asset: asset.key(),
+        shares_in: amount,
+        usdc_out: payout,
+        price: asset.price,
+        memo,
+    });
+
+    Ok(())
+}
+
+// Portion of `amount` expressed in basis points, rounded down
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    (amount as u128)
+        .checked_mul(bps as u128)
+        .unwrap()
+        .checked_div(BPS_DENOMINATOR as u128)
+        .unwrap() as u64
+}
+
+// Oracle price of one whole token in underlying base units; the feed has to
+// be fresh and its confidence interval tight
+fn oracle_price(oracle: &AccountInfo, underlying_decimals: u8) -> Result<u64> {
+    let feed = load_price_feed_from_account_info(oracle)
+        .map_err(|_| error!(ErrorCode::InvalidOracle))?;
+    let price = feed
+        .get_price_no_older_than(Clock::get()?.unix_timestamp, MAX_ORACLE_AGE as u64)
+        .ok_or(ErrorCode::StaleOracle)?;
+    require!(price.price > 0, ErrorCode::InvalidOracle);
+    require!(
+        (price.conf as u128) * (BPS_DENOMINATOR as u128)
+            <= (price.price as u128) * (MAX_ORACLE_CONF_BPS as u128),
+        ErrorCode::InvalidOracle
+    );
+
+    let exponent = price.expo + underlying_decimals as i32;
+    let value = if exponent >= 0 {
+        (price.price as u128).checked_mul(10u128.pow(exponent as u32)).unwrap()
+    } else {
+        (price.price as u128) / 10u128.pow(exponent.unsigned_abs())
+    };
+    u64::try_from(value).map_err(|_| error!(ErrorCode::InvalidOracle))
+}
+
+// Vault token account holding a basket component
+fn basket_account(vault: Pubkey, mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"basket_tokens", vault.as_ref(), mint.as_ref()], &crate::ID).0
+}
+
+// Converts a token amount between decimal precisions, rounding down
+fn rescale(amount: u64, from_decimals: u8, to_decimals: u8) -> u64 {
+    if to_decimals >= from_decimals {
+        amount
+            .checked_mul(10u64.pow((to_decimals - from_decimals) as u32))
+            .unwrap()
+    } else {
+        amount / 10u64.pow((from_decimals - to_decimals) as u32)
+    }
+}
+
+// Fee withheld when moving `amount` of a Token-2022 mint with the
+// transfer-fee extension; zero for mints without it
+fn transfer_fee(mint: &InterfaceAccount<token_interface::Mint>, amount: u64) -> Result<u64> {
+    match token_interface::get_mint_extension_data::<TransferFeeConfig>(&mint.to_account_info()) {
+        Ok(config) => Ok(config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .unwrap()),
+        Err(_) => Ok(0),
+    }
+}
+
+// Pairs are hashed in sorted order, so proofs carry no left/right flags
+fn verify_merkle_proof(root: [u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let mut node = leaf;
+    for sibling in proof {
+        node = if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        };
+    }
+    node == root
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Admin::LEN,
+        seeds = [b"admin"],
+        bump
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RoleGrant::LEN,
+        seeds = [b"role", holder.as_ref(), &[role as u8]],
+        bump
+    )]
+    pub role_grant: Account<'info, RoleGrant>,
+    
+    pub authority_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"role", role_grant.holder.as_ref(), &[role_grant.role as u8]],
+        bump,
+    )]
+    pub role_grant: Account<'info, RoleGrant>,
+    
+    pub authority_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RotateSigner<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Blacklist::LEN,
+        seeds = [b"blacklist", wallet.as_ref()],
+        bump
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"blacklist", blacklist.wallet.as_ref()],
+        bump,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeShareAccount<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = asset_mint,
+    )]
+    pub share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: blacklist entry for the share account owner; may be empty
+    #[account(
+        seeds = [b"blacklist", share_account.owner.as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct HolderFreeze<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = asset_mint,
+    )]
+    pub share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveConfidentialAccount<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        address = asset.mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = asset_mint,
+    )]
+    pub share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; an initialized account means the owner is blacklisted
+    #[account(
+        seeds = [b"blacklist", share_account.owner.as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureConfidentialMint<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitRegistry<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(zero)]
+    pub registry: AccountLoader<'info, Registry>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ListAssets<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(address = config.registry @ ErrorCode::InvalidRegistry)]
+    pub registry: AccountLoader<'info, Registry>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64, name: String, ticker: String)]
+pub struct CreateAsset<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    #[account(
+        mut,
+        address = config.registry @ ErrorCode::InvalidRegistry,
+    )]
+    pub registry: AccountLoader<'info, Registry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Asset::LEN,
+        seeds = [b"asset", underlying_mint.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", underlying_mint.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TickerClaim::LEN,
+        seeds = [b"ticker", normalize_ticker(&ticker).as_bytes()],
+        bump
+    )]
+    pub ticker_claim: Account<'info, TickerClaim>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Bond::LEN,
+        seeds = [b"bond", asset.key().as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, Bond>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Stats::LEN,
+        seeds = [b"stats", asset.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", mint.key().as_ref(), &[admin.signer_version]],
+        bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    /// CHECK: Share mint, allocated and initialized by create_asset
+    #[account(
+        mut,
+        seeds = [b"share_mint", asset.key().as_ref()],
+        bump,
+    )]
+    pub mint: UncheckedAccount<'info>,
+    
+    pub underlying_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BatchCreateAssets<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    #[account(
+        mut,
+        address = config.registry @ ErrorCode::InvalidRegistry,
+    )]
+    pub registry: AccountLoader<'info, Registry>,
+    
+    pub underlying_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats", asset.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = share_class.as_ref().map_or(asset.mint, |class| class.mint),
+        mint::token_program = token_program,
+        mint::decimals = vault.share_decimals,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub user_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer.wallet.as_ref()],
+        bump,
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub referrer_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(
+        seeds = [b"allowlist", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub credential: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"share_class", vault.key().as_ref(), share_class.mint.as_ref()],
+        bump,
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct BatchDeposit<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+    )]
+    pub user_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    pub deposit: Deposit<'info>,
+    
+    /// CHECK: wSOL account created, funded and closed within deposit_sol
+    #[account(
+        mut,
+        seeds = [b"wsol", deposit.user.key().as_ref()],
+        bump,
+    )]
+    pub wsol_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositWithSwap<'info> {
+    pub deposit: Deposit<'info>,
+    
+    #[account(
+        seeds = [b"router", router_program.key().as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: whitelisted swap router
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = deposit.user,
+        constraint = user_input_account.mint != deposit.vault.underlying_mint
+            @ ErrorCode::InvalidSwapMint,
+    )]
+    pub user_input_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct AddQuoteMint<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QuoteEntry::LEN,
+        seeds = [b"quote", vault.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub quote: Account<'info, QuoteEntry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = vault_signer,
+        token::token_program = quote_token_program,
+        seeds = [b"quote_tokens", vault.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_quote_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mint::token_program = quote_token_program)]
+    pub quote_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(class_id: u8)]
+pub struct AddShareClass<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ShareClass::LEN,
+        seeds = [b"share_class", vault.key().as_ref(), class_mint.key().as_ref()],
+        bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+    
+    /// CHECK: Class share mint, allocated and initialized by add_share_class
+    #[account(
+        mut,
+        seeds = [b"share_class_mint", vault.key().as_ref(), &[class_id]],
+        bump,
+    )]
+    pub class_mint: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateQuoteMint<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"quote", vault.key().as_ref(), quote.mint.as_ref()],
+        bump,
+    )]
+    pub quote: Account<'info, QuoteEntry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct DepositQuote<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats", asset.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+        mint::token_program = token_program,
+        mint::decimals = vault.share_decimals,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"quote", vault.key().as_ref(), quote.mint.as_ref()],
+        bump,
+    )]
+    pub quote: Account<'info, QuoteEntry>,
+    
+    #[account(
+        address = quote.mint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = quote.mint,
+    )]
+    pub user_quote_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        address = quote.token_account,
+        token::authority = vault_signer,
+    )]
+    pub vault_quote_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(
+        seeds = [b"allowlist", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub credential: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ConvertQuote<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"router", router_program.key().as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: whitelisted swap router
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"quote", vault.key().as_ref(), quote.mint.as_ref()],
+        bump,
+    )]
+    pub quote: Account<'info, QuoteEntry>,
+    
+    #[account(
+        mut,
+        address = quote.token_account,
+    )]
+    pub vault_quote_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats", asset.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = share_class.as_ref().map_or(asset.mint, |class| class.mint),
+        mint::token_program = token_program,
+        mint::decimals = vault.share_decimals,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub user_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(
+        mut,
+        seeds = [b"share_class", vault.key().as_ref(), share_class.mint.as_ref()],
+        bump,
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    /// CHECK: instructions sysvar, used to inspect the rest of the transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemToSol<'info> {
+    pub redeem: Redeem<'info>,
+    
+    /// CHECK: wSOL account created and closed within redeem_to_sol
+    #[account(
+        mut,
+        seeds = [b"wsol", redeem.user.key().as_ref()],
+        bump,
+    )]
+    pub wsol_account: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemWithSwap<'info> {
+    pub redeem: Redeem<'info>,
+    
+    #[account(
+        seeds = [b"router", router_program.key().as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: whitelisted swap router
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = redeem.user,
+        constraint = user_output_account.mint != redeem.vault.underlying_mint
+            @ ErrorCode::InvalidSwapMint,
+    )]
+    pub user_output_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SwapAssets<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [
+            b"asset",
+            from_asset.underlying_mint.as_ref(),
+            from_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub from_asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            from_asset.underlying_mint.as_ref(),
+            from_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub from_vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats", from_asset.key().as_ref()],
+        bump,
+    )]
+    pub from_stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the first vault's token accounts
+    #[account(
+        seeds = [b"vault_signer", from_asset.mint.as_ref(), &[from_vault.signer_version]],
+        bump = from_vault.signer_bump,
+    )]
+    pub from_vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = from_asset.mint,
+        mint::token_program = from_token_program,
+    )]
+    pub from_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = from_vault_signer,
+        token::mint = from_vault.underlying_mint,
+    )]
+    pub from_vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user_from_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", from_vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub from_position: Account<'info, Position>,
+    
+    #[account(
+        seeds = [
+            b"asset",
+            to_asset.underlying_mint.as_ref(),
+            to_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        constraint = to_asset.mint != from_asset.mint @ ErrorCode::InvalidSwapMint,
+    )]
+    pub to_asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            to_asset.underlying_mint.as_ref(),
+            to_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        constraint = to_vault.underlying_mint == from_vault.underlying_mint
+            @ ErrorCode::InvalidSwapMint,
+    )]
+    pub to_vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats", to_asset.key().as_ref()],
+        bump,
+    )]
+    pub to_stats: Account<'info, Stats>,
+    
+    /// CHECK: PDA that owns the second vault's token accounts and mints its shares
+    #[account(
+        seeds = [b"vault_signer", to_asset.mint.as_ref(), &[to_vault.signer_version]],
+        bump = to_vault.signer_bump,
+    )]
+    pub to_vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = to_asset.mint,
+        mint::token_program = to_token_program,
+    )]
+    pub to_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = to_vault_signer,
+        token::mint = to_vault.underlying_mint,
+    )]
+    pub to_vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub user_to_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", to_vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub to_position: Account<'info, Position>,
+    
+    #[account(
+        seeds = [b"allowlist", to_vault.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = from_vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub user: Signer<'info>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub credential: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: instructions sysvar, used to inspect the rest of the transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub from_token_program: Interface<'info, TokenInterface>,
+    pub to_token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRedeem<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + RedeemRequest::LEN,
+        seeds = [b"redeem_request", vault.key().as_ref(), vault.redeem_request_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub request: Account<'info, RedeemRequest>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(mut)]
+    pub user_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; an initialized account means the user is blacklisted
+    #[account(
+        seeds = [b"blacklist", user.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRedeem<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"redeem_request", vault.key().as_ref(), request.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Account<'info, RedeemRequest>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRedeemEpoch<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_asset_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault_usdc_account.mint,
+    )]
+    pub keeper_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RedeemEpoch::LEN,
+        seeds = [b"redeem_epoch", vault.key().as_ref(), vault.redeem_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub epoch: Account<'info, RedeemEpoch>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWithdrawal<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"redeem_request", vault.key().as_ref(), request.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Account<'info, RedeemRequest>,
+    
+    #[account(
+        seeds = [b"redeem_epoch", vault.key().as_ref(), request.epoch.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub epoch: Account<'info, RedeemEpoch>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub owner_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MintRedeemReceipt<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"redeem_request", vault.key().as_ref(), request.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Account<'info, RedeemRequest>,
+    
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"redeem_receipt", request.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = vault_signer,
+        mint::token_program = token_2022_program,
+        extensions::metadata_pointer::authority = vault_signer,
+        extensions::metadata_pointer::metadata_address = receipt_mint,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_2022_program,
+    )]
+    pub receipt_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRedeemReceipt<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        close = request_owner,
+        seeds = [b"redeem_request", vault.key().as_ref(), request.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Account<'info, RedeemRequest>,
+    
+    /// CHECK: the original requester, refunded the request rent
+    #[account(
+        mut,
+        address = request.owner,
+    )]
+    pub request_owner: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"redeem_epoch", vault.key().as_ref(), request.epoch.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub epoch: Account<'info, RedeemEpoch>,
+    
+    #[account(
+        mut,
+        address = request.receipt_mint,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = holder,
+        token::token_program = token_2022_program,
+    )]
+    pub holder_receipt_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub holder_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub holder: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct AdminBorrow<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub admin_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + WithdrawalRecord::LEN,
+        seeds = [
+            b"withdrawal_record",
+            vault.key().as_ref(),
+            vault.withdrawal_record_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub withdrawal_record: Account<'info, WithdrawalRecord>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminRepay<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub repayer_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub repayer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeWithdraw<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + WithdrawalProposal::LEN,
+        seeds = [b"withdrawal", vault.key().as_ref(), &vault.withdrawal_nonce.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    
+    #[account(token::mint = vault.underlying_mint)]
+    pub admin_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdraw<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", proposal.vault.as_ref(), &proposal.nonce.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"withdrawal", vault.key().as_ref(), &proposal.nonce.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    
+    #[account(
+        mut,
+        address = proposal.proposer,
+    )]
+    pub proposer: SystemAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        address = proposal.destination,
+    )]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + WithdrawalRecord::LEN,
+        seeds = [
+            b"withdrawal_record",
+            vault.key().as_ref(),
+            vault.withdrawal_record_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub withdrawal_record: Account<'info, WithdrawalRecord>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueueAction<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingAction::LEN,
+        seeds = [b"pending_action", config.action_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    
+    pub asset: Option<Account<'info, Asset>>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_action", pending_action.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    
+    #[account(
+        mut,
+        address = pending_action.proposer,
+    )]
+    pub proposer: SystemAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ExecuteAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_action", pending_action.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    
+    #[account(
+        mut,
+        address = pending_action.proposer,
+    )]
+    pub proposer: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub asset: Option<Account<'info, Asset>>,
+    
+    #[account(mut)]
+    pub vault: Option<Account<'info, Vault>>,
+    
+    #[account(mut)]
+    pub strategy: Option<Account<'info, Strategy>>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ExecuteWithdrawAction<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_action", pending_action.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    
+    #[account(
+        mut,
+        address = pending_action.proposer,
+    )]
+    pub proposer: SystemAccount<'info>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + WithdrawalRecord::LEN,
+        seeds = [
+            b"withdrawal_record",
+            vault.key().as_ref(),
+            vault.withdrawal_record_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub withdrawal_record: Account<'info, WithdrawalRecord>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateVault<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WhitelistAdapter<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdapterEntry::LEN,
+        seeds = [b"adapter", adapter_program.key().as_ref()],
+        bump
+    )]
+    pub adapter_entry: Account<'info, AdapterEntry>,
+    
+    /// CHECK: must be an executable program
+    pub adapter_program: UncheckedAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAdapter<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"adapter", adapter_entry.program.as_ref()],
+        bump,
+    )]
+    pub adapter_entry: Account<'info, AdapterEntry>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WhitelistRouter<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RouterEntry::LEN,
+        seeds = [b"router", router_program.key().as_ref()],
+        bump
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: must be an executable program
+    pub router_program: UncheckedAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRouter<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"router", router_entry.program.as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"router", router_program.key().as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: whitelisted swap router
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        constraint = reward_account.mint != vault_usdc_account.mint
+            && reward_account.mint != asset.mint @ ErrorCode::InvalidRewardAccount,
+    )]
+    pub reward_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; quote tokens are converted with convert_quote
+    #[account(
+        seeds = [b"quote", vault.key().as_ref(), reward_account.mint.as_ref()],
+        bump,
+        constraint = quote_entry.data_is_empty()
+            && reward_account.key() != basket_account(vault.key(), reward_account.mint)
+            @ ErrorCode::InvalidRewardAccount,
+    )]
+    pub quote_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddStrategy<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"adapter", adapter_entry.program.as_ref()],
+        bump,
+    )]
+    pub adapter_entry: Account<'info, AdapterEntry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Strategy::LEN,
+        seeds = [b"strategy", vault.key().as_ref(), adapter_entry.program.as_ref()],
+        bump
+    )]
+    pub strategy: Account<'info, Strategy>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStrategy<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"strategy", vault.key().as_ref(), strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub strategy: Account<'info, Strategy>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct StrategyFunds<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"strategy", vault.key().as_ref(), strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub strategy: Account<'info, Strategy>,
+    
+    #[account(
+        seeds = [b"adapter", strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub adapter_entry: Option<Account<'info, AdapterEntry>>,
+    
+    /// CHECK: the strategy's adapter program
+    #[account(address = strategy.adapter_program)]
+    pub adapter_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault_usdc_account.mint,
+    )]
+    pub keeper_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct Rebalance<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"strategy", vault.key().as_ref(), from_strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub from_strategy: Account<'info, Strategy>,
+    
+    /// CHECK: the source strategy's adapter program
+    #[account(address = from_strategy.adapter_program)]
+    pub from_adapter_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"strategy", vault.key().as_ref(), to_strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub to_strategy: Account<'info, Strategy>,
+    
+    #[account(
+        seeds = [b"adapter", to_strategy.adapter_program.as_ref()],
+        bump,
+    )]
+    pub to_adapter_entry: Account<'info, AdapterEntry>,
+    
+    /// CHECK: the destination strategy's adapter program
+    #[account(address = to_strategy.adapter_program)]
+    pub to_adapter_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct RebalanceBuffer<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SetVerification<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        close = treasury,
+        seeds = [b"bond", bond.asset.as_ref()],
+        bump,
+    )]
+    pub bond: Account<'info, Bond>,
+    
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct TransferAssetAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SetAssetGovernance<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    /// CHECK: ownership by the governance program is verified in the handler
+    pub governance: UncheckedAccount<'info>,
+    
+    /// CHECK: must be an executable program; owns the governance account
+    pub governance_program: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, ticker: String, uri: String)]
+pub struct UpdateAssetMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+        // Name and ticker are bounded inside LEN; only the URI grows the account
+        realloc = 8 + Asset::LEN + uri.len(),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncShareMetadata<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+        mint::token_program = token_program,
+        extensions::metadata_pointer::metadata_address = asset_mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitBasket<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Basket::LEN,
+        seeds = [b"basket", vault.key().as_ref()],
+        bump
+    )]
+    pub basket: Account<'info, Basket>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddBasketComponent<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"basket", vault.key().as_ref()],
+        bump,
+    )]
+    pub basket: Account<'info, Basket>,
+    
+    #[account(mint::token_program = token_program)]
+    pub component_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    /// CHECK: parsed as a Pyth price feed
+    pub oracle: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = component_mint,
+        token::authority = vault_signer,
+        token::token_program = token_program,
+        seeds = [b"basket_tokens", vault.key().as_ref(), component_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_component_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct MarkBasket<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"basket", vault.key().as_ref()],
+        bump,
+    )]
+    pub basket: Account<'info, Basket>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct RebalanceBasket<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"basket", vault.key().as_ref()],
+        bump,
+    )]
+    pub basket: Account<'info, Basket>,
+    
+    #[account(
+        seeds = [b"router", router_program.key().as_ref()],
+        bump,
+    )]
+    pub router_entry: Account<'info, RouterEntry>,
+    
+    /// CHECK: whitelisted swap router
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+    )]
+    pub vault_component_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LinkChildVault<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        seeds = [
+            b"asset",
+            child_asset.underlying_mint.as_ref(),
+            child_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        constraint = child_asset.mint == vault.underlying_mint @ ErrorCode::InvalidChildVault,
+    )]
+    pub child_asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [
+            b"vault",
+            child_asset.underlying_mint.as_ref(),
+            child_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub child_vault: Account<'info, Vault>,
+    
+    #[account(address = child_asset.mint)]
+    pub child_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SyncMetaPrice<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = child_vault @ ErrorCode::InvalidChildVault,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        seeds = [
+            b"asset",
+            child_asset.underlying_mint.as_ref(),
+            child_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub child_asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [
+            b"vault",
+            child_asset.underlying_mint.as_ref(),
+            child_asset.vault_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub child_vault: Account<'info, Vault>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateAssetParams<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct AcceptAssetAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAsset<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"ticker", normalize_ticker(&asset.ticker).as_bytes()],
+        bump,
+    )]
+    pub ticker_claim: Account<'info, TickerClaim>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"stats", asset.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    // Closing in good standing returns the bond; a slashed bond is gone
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"bond", asset.key().as_ref()],
+        bump,
+    )]
+    pub bond: Option<Account<'info, Bond>>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SetVaultPause<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Position::LEN,
+        seeds = [b"position", vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncPositionBalance<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", vault.key().as_ref(), position.owner.as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(
+        associated_token::mint = asset.mint,
+        associated_token::authority = position.owner,
+        associated_token::token_program = token_program,
+    )]
+    pub share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"position", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    
+    #[account(
+        associated_token::mint = asset.mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitRewardPool<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RewardPool::LEN,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = usdc_mint,
+        token::authority = reward_pool,
+        token::token_program = usdc_token_program,
+        seeds = [b"reward_pool_usdc", vault.key().as_ref()],
+        bump
+    )]
+    pub pool_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = asset_mint,
+        token::authority = reward_pool,
+        seeds = [b"reward_pool_shares", vault.key().as_ref()],
+        bump
+    )]
+    pub pool_share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenYieldCheckpoint<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + YieldCheckpoint::LEN,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseYieldCheckpoint<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct StakeShares<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_shares", vault.key().as_ref()],
+        bump,
+    )]
+    pub pool_share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+    )]
+    pub owner_share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct DistributeYield<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_usdc", vault.key().as_ref()],
+        bump,
+        token::mint = vault_usdc_account.mint,
+    )]
+    pub pool_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimYield<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_usdc", vault.key().as_ref()],
+        bump,
+        token::mint = vault.underlying_mint,
+    )]
+    pub pool_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+    )]
+    pub owner_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; an initialized account means the owner is blacklisted
+    #[account(
+        seeds = [b"blacklist", owner.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        mut,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        mut,
+        seeds = [b"yield", vault.key().as_ref(), checkpoint.owner.as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+}
+
+#[derive(Accounts)]
+pub struct InitEmissions<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = reward_mint,
+        token::authority = reward_pool,
+        seeds = [b"reward_pool_emissions", vault.key().as_ref()],
+        bump
+    )]
+    pub emissions_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct FundEmissions<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        address = reward_pool.reward_mint,
+        mint::token_program = token_program,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_emissions", vault.key().as_ref()],
+        bump,
+    )]
+    pub emissions_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = reward_pool.reward_mint,
+    )]
+    pub funder_reward_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", vault.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    
+    #[account(
+        address = reward_pool.reward_mint,
+        mint::token_program = token_program,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_emissions", vault.key().as_ref()],
+        bump,
+    )]
+    pub emissions_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"yield", vault.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, YieldCheckpoint>,
+    
+    #[account(
+        mut,
+        token::mint = reward_pool.reward_mint,
+    )]
+    pub owner_reward_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; an initialized account means the owner is blacklisted
+    #[account(
+        seeds = [b"blacklist", owner.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateGauge<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Gauge::LEN,
+        seeds = [b"gauge", vault.key().as_ref()],
+        bump
+    )]
+    pub gauge: Account<'info, Gauge>,
+    
+    /// CHECK: PDA that owns the vote-escrowed shares
+    #[account(
+        seeds = [b"ve_authority", vault.key().as_ref()],
+        bump,
+    )]
+    pub ve_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = asset_mint,
+        token::authority = ve_authority,
+        seeds = [b"ve_escrow", vault.key().as_ref()],
+        bump
+    )]
+    pub ve_escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct LockShares<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VeLock::LEN,
+        seeds = [b"ve_lock", vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub ve_lock: Account<'info, VeLock>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"ve_escrow", vault.key().as_ref()],
+        bump,
+    )]
+    pub ve_escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+    )]
+    pub owner_share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockShares<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"ve_lock", vault.key().as_ref(), owner.key().as_ref()],
+        bump = ve_lock.bump,
+    )]
+    pub ve_lock: Account<'info, VeLock>,
+    
+    /// CHECK: PDA that owns the vote-escrowed shares
+    #[account(
+        seeds = [b"ve_authority", vault.key().as_ref()],
+        bump,
+    )]
+    pub ve_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"ve_escrow", vault.key().as_ref()],
+        bump,
+    )]
+    pub ve_escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = asset.mint,
+    )]
+    pub owner_share_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct VoteGauge<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"gauge", gauge.vault.as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Account<'info, Gauge>,
+    
+    #[account(
+        mut,
+        seeds = [b"ve_lock", ve_lock.vault.as_ref(), owner.key().as_ref()],
+        bump = ve_lock.bump,
+    )]
+    pub ve_lock: Account<'info, VeLock>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ResetVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"gauge", ve_lock.voted_vault.as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Account<'info, Gauge>,
+    
+    #[account(
+        mut,
+        seeds = [b"ve_lock", ve_lock.vault.as_ref(), owner.key().as_ref()],
+        bump = ve_lock.bump,
+    )]
+    pub ve_lock: Account<'info, VeLock>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct RefreshVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"gauge", ve_lock.voted_vault.as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Account<'info, Gauge>,
+    
+    #[account(
+        mut,
+        seeds = [b"ve_lock", ve_lock.vault.as_ref(), ve_lock.owner.as_ref()],
+        bump = ve_lock.bump,
+    )]
+    pub ve_lock: Account<'info, VeLock>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ApplyGauge<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"gauge", gauge.vault.as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Account<'info, Gauge>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool", gauge.vault.as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct CreateDistribution<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Distribution::LEN,
+        seeds = [b"distribution", vault.key().as_ref(), vault.distribution_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = distribution,
+        seeds = [b"distribution_tokens", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub funder_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct ClaimDistribution<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution", vault.key().as_ref(), distribution.nonce.to_le_bytes().as_ref()],
+        bump = distribution.bump,
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(
+        address = distribution.mint,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_tokens", distribution.key().as_ref()],
+        bump,
+    )]
+    pub distribution_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + DistributionClaim::LEN,
+        seeds = [b"distribution_claim", distribution.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, DistributionClaim>,
+    
+    #[account(
+        mut,
+        token::mint = distribution.mint,
+    )]
+    pub claimant_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: must be empty; an initialized account means the claimant is blacklisted
+    #[account(
+        seeds = [b"blacklist", claimant.key().as_ref()],
+        bump,
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AllowlistEntry::LEN,
+        seeds = [b"allowlist", vault.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", vault.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitTransferHook<'info> {
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    /// CHECK: extra account metas for the share mint's transfer hook
+    #[account(
+        init,
+        payer = authority,
+        space = ExtraAccountMetaList::size_of(5)?,
+        seeds = [b"extra-account-metas", asset.mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// Account order is fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(token::mint = mint)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: source owner or delegate, verified by the token program
+    pub owner: UncheckedAccount<'info>,
+    
+    /// CHECK: extra account metas for the share mint's transfer hook
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint,
+        has_one = vault,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: must be empty; an initialized account means the sender is blacklisted
+    #[account(
+        seeds = [b"blacklist", source.owner.as_ref()],
+        bump,
+    )]
+    pub source_blacklist_entry: UncheckedAccount<'info>,
+    
+    /// CHECK: must be empty; an initialized account means the recipient is blacklisted
+    #[account(
+        seeds = [b"blacklist", destination.owner.as_ref()],
+        bump,
+    )]
+    pub destination_blacklist_entry: UncheckedAccount<'info>,
+    
+    /// CHECK: initialized only if the recipient is allowlisted
+    #[account(
+        seeds = [b"allowlist", vault.key().as_ref(), destination.owner.as_ref()],
+        bump,
+    )]
+    pub destination_allowlist_entry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
         init,
-        payer = authority,
-        space = 8 + Vault::LEN,
-        seeds = [b"vault", mint.key().as_ref()],
+        payer = wallet,
+        space = 8 + Referrer::LEN,
+        seeds = [b"referrer", wallet.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, Vault>,
+    pub referrer: Account<'info, Referrer>,
     
     #[account(mut)]
-    pub mint: Account<'info, Mint>,
+    pub wallet: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = usdc_token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::mint = vault.underlying_mint,
+        token::authority = vault_signer,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = fee_recipient_usdc_account.owner == vault.fee_recipient @ ErrorCode::InvalidFeeRecipient,
+        token::mint = vault.underlying_mint,
+    )]
+    pub fee_recipient_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    
+    #[account(
+        mut,
+        constraint = fee_recipient_asset_account.owner == vault.fee_recipient @ ErrorCode::InvalidFeeRecipient,
+    )]
+    pub fee_recipient_asset_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
     
-    #[account(mut)]
     pub authority: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SyncVault<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mint::token_program = token_program,
+        address = vault.underlying_mint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault_usdc_account.mint,
+        token::authority = config.treasury,
+    )]
+    pub treasury_usdc_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct SweepTokens<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: PDA that owns the vault token accounts and mints asset tokens
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        token::authority = vault_signer,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        token::authority = vault_signer,
+    )]
+    pub source: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        address = source.mint,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    /// CHECK: must be empty; quote tokens back shares and can't be swept
+    #[account(
+        seeds = [b"quote", vault.key().as_ref(), source.mint.as_ref()],
+        bump,
+    )]
+    pub quote_entry: UncheckedAccount<'info>,
+    
+    /// CHECK: must be empty; strategy positions can't be swept
+    #[account(
+        seeds = [b"strategy_account", vault.key().as_ref(), source.key().as_ref()],
+        bump,
+    )]
+    pub strategy_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = source.mint,
+        token::authority = config.treasury,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVaultSigner<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump,
+    )]
+    pub admin: Account<'info, Admin>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        seeds = [b"asset", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", asset.underlying_mint.as_ref(), asset.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// CHECK: signer PDA the vault is currently registered with
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[vault.signer_version]],
+        bump = vault.signer_bump,
+    )]
+    pub old_vault_signer: UncheckedAccount<'info>,
+    
+    /// CHECK: signer PDA for the protocol's current signer version
+    #[account(
+        seeds = [b"vault_signer", asset.mint.as_ref(), &[admin.signer_version]],
+        bump,
+    )]
+    pub new_vault_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = asset.mint,
+        mint::token_program = token_program,
+    )]
+    pub asset_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        mut,
+        constraint = vault_usdc_account.owner == old_vault_signer.key() @ ErrorCode::Unauthorized,
+        token::mint = vault.underlying_mint,
+    )]
+    pub vault_usdc_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    // Index vaults move every basket component account with the signer
+    #[account(
+        seeds = [b"basket", vault.key().as_ref()],
+        bump,
+    )]
+    pub basket: Option<Account<'info, Basket>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+}
+
+#[account]
+pub struct Admin {
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    pub signer_version: u8,
+    pub rotation_unlock_ts: i64,
+    pub pending_authority: Pubkey,
+    pub authorities: [Pubkey; MAX_AUTHORITIES],
+    pub withdraw_threshold: u8,
+}
+
+impl Admin {
+    pub const LEN: usize = 32 // authority (Pubkey)
+        + 32 // guardian (Pubkey)
+        + 1 // signer_version (u8)
+        + 8 // rotation_unlock_ts (i64)
+        + 32 // pending_authority (Pubkey)
+        + 32 * MAX_AUTHORITIES // authorities ([Pubkey; MAX_AUTHORITIES])
+        + 1; // withdraw_threshold (u8)
+
+    // Root authority or one of the co-admins
+    pub fn is_authority(&self, key: Pubkey) -> bool {
+        key != Pubkey::default() && (self.authority == key || self.authorities.contains(&key))
+    }
+
+    pub fn is_renounced(&self) -> bool {
+        self.authority == Pubkey::default()
+    }
+
+    // Root authority plus every occupied co-admin slot
+    pub fn authority_count(&self) -> usize {
+        1 + self
+            .authorities
+            .iter()
+            .filter(|key| **key != Pubkey::default())
+            .count()
+    }
+
+    // Admin authorities implicitly hold every role; anyone else needs a
+    // matching grant, and a SuperAdmin grant covers every role
+    pub fn has_role(&self, signer: Pubkey, role: Role, grant: Option<&RoleGrant>) -> bool {
+        if self.is_renounced() {
+            return false;
+        }
+        if self.is_authority(signer) {
+            return true;
+        }
+        grant.is_some_and(|grant| {
+            grant.holder == signer && (grant.role == role || grant.role == Role::SuperAdmin)
+        })
+    }
+}
+
+#[account]
+pub struct RoleGrant {
+    pub holder: Pubkey,
+    pub role: Role,
+}
+
+impl RoleGrant {
+    pub const LEN: usize = 32 + 1; // holder (Pubkey) + role (Role)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    SuperAdmin,
+    VaultManager,
+    FeeCollector,
+    Pauser,
+    Compliance,
+    Keeper,
+}
+
+#[account]
+pub struct Config {
+    pub creation_fee: u64,
+    pub treasury: Pubkey,
+    pub global_pause: bool,
+    pub timelock_delay: i64,
+    pub action_nonce: u64,
+    pub min_initial_deposit: u64,
+    pub gauge_emission_rate: u64,
+    pub gauge_total_weight: u64,
+    pub registry: Pubkey,
+    pub listing_bond: u64,
+}
+
+impl Config {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 32 + 8; // creation_fee (u64) + treasury (Pubkey) + global_pause (bool) + timelock_delay (i64) + action_nonce (u64) + min_initial_deposit (u64) + gauge_emission_rate (u64) + gauge_total_weight (u64) + registry (Pubkey) + listing_bond (u64)
+}
+
+// Append-only list of every asset created, readable without scanning
+// program accounts. Closed assets keep their entry
+#[account(zero_copy)]
+pub struct Registry {
+    pub count: u64,
+    pub entries: [RegistryEntry; MAX_REGISTRY_ENTRIES],
+}
+
+impl Registry {
+    pub const LEN: usize = 8 // count (u64)
+        + MAX_REGISTRY_ENTRIES * RegistryEntry::LEN; // entries ([RegistryEntry; MAX_REGISTRY_ENTRIES])
+
+    pub fn push(&mut self, key: Pubkey, asset: &Asset) -> Result<()> {
+        let index = self.count as usize;
+        require!(index < MAX_REGISTRY_ENTRIES, ErrorCode::RegistryFull);
+
+        let entry = &mut self.entries[index];
+        entry.asset = key;
+        entry.mint = asset.mint;
+        entry.ticker[..asset.ticker.len()].copy_from_slice(asset.ticker.as_bytes());
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn page(&self, offset: u64, limit: u64) -> &[RegistryEntry] {
+        let count = self.count as usize;
+        let start = (offset as usize).min(count);
+        let end = start.saturating_add(limit as usize).min(count);
+        &self.entries[start..end]
+    }
+}
+
+// Ticker is zero-padded UTF-8
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RegistryEntry {
+    pub asset: Pubkey,
+    pub mint: Pubkey,
+    pub ticker: [u8; 16],
+}
+
+impl RegistryEntry {
+    pub const LEN: usize = 32 // asset (Pubkey)
+        + 32 // mint (Pubkey)
+        + 16; // ticker ([u8; 16])
+}
+
+#[account]
+pub struct PendingAction {
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub vault: Pubkey,
+    pub action: TimelockedAction,
+    pub eta: i64,
+}
+
+impl PendingAction {
+    pub const LEN: usize = 8 + 32 + 32 + TimelockedAction::LEN + 8; // nonce (u64) + proposer (Pubkey) + vault (Pubkey) + action (TimelockedAction) + eta (i64)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockedAction {
+    SetFees {
+        deposit_fee_bps: u16,
+        redeem_fee_bps: u16,
+        fee_recipient: Pubkey,
+        fee_mode: FeeMode,
+    },
+    SetReferralFee {
+        referral_fee_bps: u16,
+    },
+    SetWithdrawCap {
+        withdraw_cap_bps: u16,
+    },
+    SetBorrowRate {
+        borrow_rate_bps: u16,
+    },
+    SetMinCollateral {
+        min_collateral_bps: u16,
+    },
+    AdminWithdraw {
+        amount: u64,
+        destination: Pubkey,
+    },
+    SetTimelockDelay {
+        delay: i64,
+    },
+    Delist {
+        settlement_price: u64,
+        window: i64,
+    },
+    SetDepositLimit {
+        deposit_limit: u64,
+    },
+    SetPerUserCap {
+        per_user_cap: u64,
+    },
+    SetMinDeposit {
+        min_deposit: u64,
+    },
+    SetDustThreshold {
+        dust_threshold: u64,
+        sweep_dust: bool,
+    },
+    SetRedeemCooldown {
+        redeem_cooldown_slots: u64,
+    },
+    SetRedeemEpochDuration {
+        redeem_epoch_duration: i64,
+    },
+    SetRedeemRateLimit {
+        redeem_rate_limit: u64,
+    },
+    SetDepositInflowCap {
+        inflow_cap: u64,
+        inflow_window_slots: u64,
+    },
+    SetBufferTarget {
+        buffer_target_bps: u16,
+    },
+    SetPerformanceFee {
+        performance_fee_bps: u16,
+    },
+    SetProfitUnlockPeriod {
+        period: i64,
+    },
+    SetRebalanceCap {
+        rebalance_cap_bps: u16,
+    },
+    SetKeeperTip {
+        keeper_tip: u64,
+        keeper_tip_bps: u16,
+    },
+    SetDonationPolicy {
+        sweep_donations: bool,
+    },
+    SetCreationFee {
+        creation_fee: u64,
+        treasury: Pubkey,
+    },
+    SetMinInitialDeposit {
+        min_initial_deposit: u64,
+    },
+    SetListingBond {
+        listing_bond: u64,
+    },
+    SetGaugeEmissionRate {
+        gauge_emission_rate: u64,
+    },
+    SetStrategyLimits {
+        adapter_program: Pubkey,
+        max_debt: u64,
+        debt_ratio_bps: u16,
+    },
+}
+
+impl TimelockedAction {
+    pub const LEN: usize = 1 + 32 + 8 + 2; // variant + largest payload (SetStrategyLimits)
+}
+
+#[account]
+pub struct Asset {
+    pub name: String,
+    pub ticker: String,
+    pub price: u64,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub underlying_mint: Pubkey,
+    pub vault_id: u64,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub governance_program: Pubkey,
+    pub delisted: bool,
+    pub settlement_deadline: i64,
+    pub verified: bool,
+    pub category: AssetCategory,
+    pub tags: u16,
+    pub uri: String,
+}
+
+impl Asset {
+    pub const LEN: usize = 4 + 50 // name (String)
+        + 4 + 10 // ticker (String)
+        + 8 // price (u64)
+        + 32 // mint (Pubkey)
+        + 32 // vault (Pubkey)
+        + 32 // underlying_mint (Pubkey)
+        + 8 // vault_id (u64)
+        + 32 // authority (Pubkey)
+        + 32 // pending_authority (Pubkey)
+        + 32 // governance_program (Pubkey)
+        + 1 // delisted (bool)
+        + 8 // settlement_deadline (i64)
+        + 1 // verified (bool)
+        + 1 // category (AssetCategory)
+        + 2 // tags (u16)
+        + 4; // uri (String), contents are allocated on update
+
+    // Freeze the price at its final settlement value; holders can redeem at
+    // that price until the wind-down window closes
+    pub fn delist(&mut self, settlement_price: u64, window: i64, now: i64) -> Result<()> {
+        require!(!self.delisted, ErrorCode::AssetDelisted);
+
+        self.price = settlement_price;
+        self.delisted = true;
+        self.settlement_deadline = now.checked_add(window).unwrap();
+        Ok(())
+    }
+}
+
+// Reserves a normalized ticker for one asset until it is closed
+#[account]
+pub struct TickerClaim {
+    pub asset: Pubkey,
+}
+
+impl TickerClaim {
+    pub const LEN: usize = 32; // asset (Pubkey)
+}
+
+// Lifetime activity through deposit and redeem, in underlying units
+#[account]
+pub struct Stats {
+    pub asset: Pubkey,
+    pub deposit_volume: u64,
+    pub redeem_volume: u64,
+    pub tvl_high: u64,
+    pub deposit_count: u64,
+    pub redeem_count: u64,
+}
+
+impl Stats {
+    pub const LEN: usize = 32 // asset (Pubkey)
+        + 8 // deposit_volume (u64)
+        + 8 // redeem_volume (u64)
+        + 8 // tvl_high (u64)
+        + 8 // deposit_count (u64)
+        + 8; // redeem_count (u64)
+
+    pub fn record_deposit(&mut self, amount: u64, tvl: u64) {
+        self.deposit_volume = self.deposit_volume.checked_add(amount).unwrap();
+        self.deposit_count = self.deposit_count.checked_add(1).unwrap();
+        self.tvl_high = self.tvl_high.max(tvl);
+    }
+
+    pub fn record_redeem(&mut self, amount: u64) {
+        self.redeem_volume = self.redeem_volume.checked_add(amount).unwrap();
+        self.redeem_count = self.redeem_count.checked_add(1).unwrap();
+    }
+}
+
+// Lamports escrowed by an asset's creator, on top of the account's rent
+#[account]
+pub struct Bond {
+    pub asset: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+impl Bond {
+    pub const LEN: usize = 32 // asset (Pubkey)
+        + 32 // creator (Pubkey)
+        + 8; // amount (u64)
+}
+
+#[account]
+pub struct Vault {
+    pub total_usdc: u64,
+    pub total_assets: u64,
+    pub deposit_limit: u64,
+    pub signer_version: u8,
+    pub signer_bump: u8,
+    pub fee_recipient: Pubkey,
+    pub deposit_fee_bps: u16,
+    pub redeem_fee_bps: u16,
+    pub fees_outstanding: u64,
+    pub fee_mode: FeeMode,
+    pub fee_shares_outstanding: u64,
+    pub referral_fee_bps: u16,
+    pub pause_flags: u8,
+    pub withdrawal_nonce: u64,
+    pub withdraw_cap_bps: u16,
+    pub withdraw_epoch_start: i64,
+    pub withdraw_epoch_base: u64,
+    pub withdrawn_this_epoch: u64,
+    pub admin_debt: u64,
+    pub admin_borrow_rate_bps: u16,
+    pub debt_accrual_ts: i64,
+    pub min_collateral_bps: u16,
+    pub total_admin_withdrawn: u64,
+    pub withdrawal_record_count: u64,
+    pub shutdown: bool,
+    pub per_user_cap: u64,
+    pub min_deposit: u64,
+    pub allowlist_enabled: bool,
+    pub allowlist_root: [u8; 32],
+    pub credential_mint: Pubkey,
+    pub redeem_cooldown_slots: u64,
+    pub redeem_epoch: u64,
+    pub redeem_epoch_start: i64,
+    pub redeem_epoch_duration: i64,
+    pub redeem_request_nonce: u64,
+    pub queued_redeem_shares: u64,
+    pub claimable_usdc: u64,
+    pub queued_redeem_requests: u64,
+    pub redeem_rate_limit: u64,
+    pub redeem_bucket: u64,
+    pub redeem_bucket_ts: i64,
+    pub inflow_cap: u64,
+    pub inflow_window_slots: u64,
+    pub inflow_window_start: u64,
+    pub inflow_this_window: u64,
+    pub buffer_target_bps: u16,
+    pub strategy_debt: u64,
+    pub strategy_gain: u64,
+    pub total_debt_ratio_bps: u16,
+    pub performance_fee_bps: u16,
+    pub profit_unlock_period: i64,
+    pub locked_profit: u64,
+    pub locked_profit_ts: i64,
+    pub rebalance_cap_bps: u16,
+    pub keeper_tip: u64,
+    pub keeper_tip_bps: u16,
+    pub automation_thread: Pubkey,
+    pub distribution_nonce: u64,
+    pub dust_threshold: u64,
+    pub sweep_dust: bool,
+    pub sweep_donations: bool,
+    pub underlying_mint: Pubkey,
+    pub underlying_decimals: u8,
+    pub quote_backing: u64,
+    pub share_decimals: u8,
+    pub child_vault: Pubkey,
+    pub index_vault: bool,
+    pub basket_value: u64,
+    pub basket_marked_at: i64,
+}
+
+impl Vault {
+    pub const LEN: usize = 8 // total_usdc (u64)
+        + 8 // total_assets (u64)
+        + 8 // deposit_limit (u64)
+        + 1 // signer_version (u8)
+        + 1 // signer_bump (u8)
+        + 32 // fee_recipient (Pubkey)
+        + 2 // deposit_fee_bps (u16)
+        + 2 // redeem_fee_bps (u16)
+        + 8 // fees_outstanding (u64)
+        + 1 // fee_mode (FeeMode)
+        + 8 // fee_shares_outstanding (u64)
+        + 2 // referral_fee_bps (u16)
+        + 1 // pause_flags (u8)
+        + 8 // withdrawal_nonce (u64)
+        + 2 // withdraw_cap_bps (u16)
+        + 8 // withdraw_epoch_start (i64)
+        + 8 // withdraw_epoch_base (u64)
+        + 8 // withdrawn_this_epoch (u64)
+        + 8 // admin_debt (u64)
+        + 2 // admin_borrow_rate_bps (u16)
+        + 8 // debt_accrual_ts (i64)
+        + 2 // min_collateral_bps (u16)
+        + 8 // total_admin_withdrawn (u64)
+        + 8 // withdrawal_record_count (u64)
+        + 1 // shutdown (bool)
+        + 8 // per_user_cap (u64)
+        + 8 // min_deposit (u64)
+        + 1 // allowlist_enabled (bool)
+        + 32 // allowlist_root ([u8; 32])
+        + 32 // credential_mint (Pubkey)
+        + 8 // redeem_cooldown_slots (u64)
+        + 8 // redeem_epoch (u64)
+        + 8 // redeem_epoch_start (i64)
+        + 8 // redeem_epoch_duration (i64)
+        + 8 // redeem_request_nonce (u64)
+        + 8 // queued_redeem_shares (u64)
+        + 8 // claimable_usdc (u64)
+        + 8 // queued_redeem_requests (u64)
+        + 8 // redeem_rate_limit (u64)
+        + 8 // redeem_bucket (u64)
+        + 8 // redeem_bucket_ts (i64)
+        + 8 // inflow_cap (u64)
+        + 8 // inflow_window_slots (u64)
+        + 8 // inflow_window_start (u64)
+        + 8 // inflow_this_window (u64)
+        + 2 // buffer_target_bps (u16)
+        + 8 // strategy_debt (u64)
+        + 8 // strategy_gain (u64)
+        + 2 // total_debt_ratio_bps (u16)
+        + 2 // performance_fee_bps (u16)
+        + 8 // profit_unlock_period (i64)
+        + 8 // locked_profit (u64)
+        + 8 // locked_profit_ts (i64)
+        + 2 // rebalance_cap_bps (u16)
+        + 8 // keeper_tip (u64)
+        + 2 // keeper_tip_bps (u16)
+        + 32 // automation_thread (Pubkey)
+        + 8 // distribution_nonce (u64)
+        + 8 // dust_threshold (u64)
+        + 1 // sweep_dust (bool)
+        + 1 // sweep_donations (bool)
+        + 32 // underlying_mint (Pubkey)
+        + 1 // underlying_decimals (u8)
+        + 8 // quote_backing (u64)
+        + 1 // share_decimals (u8)
+        + 32 // child_vault (Pubkey)
+        + 1 // index_vault (bool)
+        + 8 // basket_value (u64)
+        + 8; // basket_marked_at (i64)
+
+    // One whole share in base units; prices are quoted in underlying base
+    // units per whole share
+    pub fn unit(&self) -> u64 {
+        10u64.pow(self.share_decimals as u32)
+    }
+
+    // Index vault shares are priced off the basket, so its mark must be
+    // recent before shares are minted or redeemed
+    pub fn check_basket_fresh(&self, now: i64) -> Result<()> {
+        require!(
+            !self.index_vault || now.saturating_sub(self.basket_marked_at) <= MAX_ORACLE_AGE,
+            ErrorCode::StaleOracle
+        );
+        Ok(())
+    }
+
+    // Shares worth `amount` of USDC at `price`, rounded down
+    pub fn to_shares(&self, amount: u64, price: u64) -> u64 {
+        (amount as u128)
+            .checked_mul(self.unit() as u128)
+            .unwrap()
+            .checked_div(price as u128)
+            .unwrap() as u64
+    }
+
+    // USDC value of `shares` at `price`, rounded down
+    pub fn to_value(&self, shares: u64, price: u64) -> u64 {
+        (shares as u128)
+            .checked_mul(price as u128)
+            .unwrap()
+            .checked_div(self.unit() as u128)
+            .unwrap() as u64
+    }
+
+    // USDC value of all outstanding shares, including unminted fee shares
+    pub fn shares_value(&self, price: u64) -> u64 {
+        let shares = self.total_assets.checked_add(self.fee_shares_outstanding).unwrap();
+        self.to_value(shares, price)
+    }
+
+    // A redemption may not leave a balance below the dust threshold: the
+    // remainder is either swept into the redemption or the redemption fails
+    pub fn dust_adjusted(&self, amount: u64, balance: u64) -> Result<u64> {
+        let remaining = balance.saturating_sub(amount);
+        if remaining == 0 || remaining >= self.dust_threshold {
+            return Ok(amount);
+        }
+        require!(self.sweep_dust, ErrorCode::DustRemainder);
+        Ok(balance)
+    }
+
+    // Leaves are keccak(wallet)
+    pub fn allowlist_contains(&self, wallet: Pubkey, proof: &[[u8; 32]]) -> bool {
+        if self.allowlist_root == [0; 32] {
+            return false;
+        }
+
+        let leaf = keccak::hash(wallet.as_ref()).to_bytes();
+        verify_merkle_proof(self.allowlist_root, leaf, proof)
+    }
+
+    // Liquid USDC kept on hand for redemptions, as a share of total backing
+    pub fn buffer_target(&self) -> u64 {
+        let backing = self.total_backing().checked_add(self.admin_debt).unwrap();
+        bps_of(backing, self.buffer_target_bps)
+    }
+
+    pub fn is_automation_thread(&self, signer: Pubkey) -> bool {
+        self.automation_thread != Pubkey::default() && self.automation_thread == signer
+    }
+
+    // USDC backing the shares, whether held by the vault, deployed or held
+    // as other quote tokens or basket components
+    pub fn total_backing(&self) -> u64 {
+        self.total_usdc
+            .checked_add(self.quote_backing)
+            .unwrap()
+            .checked_add(self.basket_value)
+            .unwrap()
+            .checked_add(self.strategy_debt)
+            .unwrap()
+            .checked_add(self.strategy_gain)
+            .unwrap()
+    }
+
+    // Backing available to share pricing, excluding profit still unlocking
+    pub fn share_backing(&self, now: i64) -> u64 {
+        self.total_backing().saturating_sub(self.locked_profit(now))
+    }
+
+    // Profit recognized recently and not yet released into share pricing;
+    // it unlocks linearly over `profit_unlock_period`
+    pub fn locked_profit(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.locked_profit_ts).max(0);
+        if elapsed >= self.profit_unlock_period {
+            return 0;
+        }
+        (self.locked_profit as u128)
+            .checked_mul((self.profit_unlock_period - elapsed) as u128)
+            .unwrap()
+            .checked_div(self.profit_unlock_period as u128)
+            .unwrap() as u64
+    }
+
+    // New profit joins whatever is still locked and the release restarts
+    pub fn lock_profit(&mut self, profit: u64, now: i64) {
+        self.locked_profit = self.locked_profit(now).checked_add(profit).unwrap();
+        self.locked_profit_ts = now;
+    }
+
+    // Losses and fees come out of locked profit first
+    pub fn release_locked_profit(&mut self, amount: u64, now: i64) {
+        self.locked_profit = self.locked_profit(now).saturating_sub(amount);
+        self.locked_profit_ts = now;
+    }
+
+    // Settle what is locked under the old period before switching
+    pub fn set_profit_unlock_period(&mut self, period: i64, now: i64) {
+        self.locked_profit = self.locked_profit(now);
+        self.locked_profit_ts = now;
+        self.profit_unlock_period = period;
+    }
+
+    // Vault USDC no longer covers every share at the current price
+    pub fn in_recovery(&self, price: u64, now: i64) -> bool {
+        self.share_backing(now) < self.shares_value(price)
+    }
+
+    // USDC owed for redeeming `shares`; in recovery every holder gets the
+    // same pro-rata cut of the remaining USDC instead of the full price
+    pub fn redemption_value(&self, shares: u64, price: u64, now: i64) -> u64 {
+        if !self.in_recovery(price, now) {
+            return self.to_value(shares, price);
+        }
+
+        let supply = self.total_assets.checked_add(self.fee_shares_outstanding).unwrap();
+        (shares as u128)
+            .checked_mul(self.share_backing(now) as u128)
+            .unwrap()
+            .checked_div(supply as u128)
+            .unwrap() as u64
+    }
+
+    // Add `profit` to vault USDC net of the performance fee, which accrues in
+    // the vault's fee mode; returns the fee
+    pub fn book_profit(&mut self, profit: u64, price: u64) -> u64 {
+        let fee = bps_of(profit, self.performance_fee_bps);
+        match self.fee_mode {
+            FeeMode::Assets => {
+                self.total_usdc = self.total_usdc.checked_add(profit - fee).unwrap();
+                self.fees_outstanding = self.fees_outstanding.checked_add(fee).unwrap();
+            }
+            FeeMode::Shares => {
+                self.total_usdc = self.total_usdc.checked_add(profit).unwrap();
+                let fee_shares = self.to_shares(fee, price);
+                self.fee_shares_outstanding =
+                    self.fee_shares_outstanding.checked_add(fee_shares).unwrap();
+            }
+        }
+        fee
+    }
+
+    // Retire redeemed shares; in share-fee mode the fee stays in the vault as
+    // backing for newly accrued fee shares, at the redeem fee actually
+    // charged (the vault's or a share class's)
+    pub fn book_redemption(
+        &mut self,
+        shares: u64,
+        usdc_amount: u64,
+        fee: u64,
+        redeem_fee_bps: u16,
+    ) {
+        self.total_assets = self.total_assets.checked_sub(shares).unwrap();
+        match self.fee_mode {
+            FeeMode::Assets => {
+                self.total_usdc = self.total_usdc.checked_sub(usdc_amount).unwrap();
+                self.fees_outstanding = self.fees_outstanding.checked_add(fee).unwrap();
+            }
+            FeeMode::Shares => {
+                let payout = usdc_amount.checked_sub(fee).unwrap();
+                self.total_usdc = self.total_usdc.checked_sub(payout).unwrap();
+                let fee_shares = bps_of(shares, redeem_fee_bps);
+                self.fee_shares_outstanding =
+                    self.fee_shares_outstanding.checked_add(fee_shares).unwrap();
+            }
+        }
+    }
+
+    // Token bucket holding up to `redeem_rate_limit` USDC, refilled linearly
+    // over REDEEM_RATE_WINDOW; larger exits have to use the withdrawal queue
+    pub fn consume_redeem_capacity(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.redeem_rate_limit == 0 {
+            return Ok(());
+        }
+
+        let elapsed = now.saturating_sub(self.redeem_bucket_ts).max(0) as u128;
+        let refill = (self.redeem_rate_limit as u128)
+            .checked_mul(elapsed)
+            .unwrap()
+            .checked_div(REDEEM_RATE_WINDOW as u128)
+            .unwrap();
+        let available = (self.redeem_bucket as u128)
+            .checked_add(refill)
+            .unwrap()
+            .min(self.redeem_rate_limit as u128) as u64;
+        require!(amount <= available, ErrorCode::RedeemRateLimited);
+
+        self.redeem_bucket = available - amount;
+        self.redeem_bucket_ts = now;
+        Ok(())
+    }
+
+    // Cap on USDC deposited per window of `inflow_window_slots` slots
+    pub fn record_inflow(&mut self, amount: u64, slot: u64) -> Result<()> {
+        if self.inflow_cap == 0 {
+            return Ok(());
+        }
+
+        if slot >= self.inflow_window_start.checked_add(self.inflow_window_slots).unwrap() {
+            self.inflow_window_start = slot;
+            self.inflow_this_window = 0;
+        }
+
+        let inflow = self.inflow_this_window.checked_add(amount).unwrap();
+        require!(inflow <= self.inflow_cap, ErrorCode::InflowCapExceeded);
+        self.inflow_this_window = inflow;
+        Ok(())
+    }
+
+    pub fn set_redeem_rate_limit(&mut self, redeem_rate_limit: u64, now: i64) {
+        self.redeem_rate_limit = redeem_rate_limit;
+        self.redeem_bucket = redeem_rate_limit;
+        self.redeem_bucket_ts = now;
+    }
+
+    // Simple interest on outstanding admin debt since the last accrual
+    pub fn accrue_admin_interest(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.debt_accrual_ts).max(0) as u128;
+        let interest = (self.admin_debt as u128)
+            .checked_mul(self.admin_borrow_rate_bps as u128)
+            .unwrap()
+            .checked_mul(elapsed)
+            .unwrap()
+            .checked_div(BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128)
+            .unwrap() as u64;
+
+        self.admin_debt = self.admin_debt.checked_add(interest).unwrap();
+        self.debt_accrual_ts = now;
+    }
+
+    // USDC moved out by the admins is tracked as debt owed back to the vault,
+    // so total_usdc + admin_debt stays equal to the backing of the shares
+    pub fn apply_admin_borrow(&mut self, amount: u64, price: u64, now: i64) -> Result<()> {
+        require!(!self.shutdown, ErrorCode::VaultShutdown);
+        self.accrue_admin_interest(now);
+
+        // Start a new window, capping against the backing at its start
+        if now >= self.withdraw_epoch_start.checked_add(WITHDRAW_EPOCH_DURATION).unwrap() {
+            self.withdraw_epoch_start = now;
+            self.withdraw_epoch_base = self.total_usdc;
+            self.withdrawn_this_epoch = 0;
+        }
+
+        let withdrawn = self.withdrawn_this_epoch.checked_add(amount).unwrap();
+        require!(
+            withdrawn <= bps_of(self.withdraw_epoch_base, self.withdraw_cap_bps),
+            ErrorCode::WithdrawCapExceeded
+        );
+
+        // Remaining USDC must still cover the shares at the required ratio
+        let remaining = self.total_usdc.checked_sub(amount).unwrap();
+        let required = bps_of(self.shares_value(price), self.min_collateral_bps);
+        require!(remaining >= required, ErrorCode::InsufficientCollateral);
+
+        // Only USDC above the liquidity buffer can be deployed
+        require!(
+            remaining >= self.buffer_target(),
+            ErrorCode::BufferTargetBreached
+        );
+
+        self.withdrawn_this_epoch = withdrawn;
+        self.total_usdc = remaining;
+        self.admin_debt = self.admin_debt.checked_add(amount).unwrap();
+        Ok(())
+    }
+
+    // Append an entry to the on-chain ledger of admin withdrawals
+    pub fn log_admin_withdrawal(
+        &mut self,
+        record: &mut WithdrawalRecord,
+        amount: u64,
+        destination: Pubkey,
+        authority: Pubkey,
+        reason: String,
+    ) -> Result<()> {
+        require!(reason.len() <= MAX_REASON_LEN, ErrorCode::ReasonTooLong);
+
+        record.index = self.withdrawal_record_count;
+        record.amount = amount;
+        record.destination = destination;
+        record.authority = authority;
+        record.slot = Clock::get()?.slot;
+        record.reason = reason;
+
+        self.total_admin_withdrawn = self.total_admin_withdrawn.checked_add(amount).unwrap();
+        self.withdrawal_record_count = self.withdrawal_record_count.checked_add(1).unwrap();
+        Ok(())
+    }
+}
+
+// Marks a vault-signer token account as holding a strategy's position
+#[account]
+pub struct StrategyAccount {
+    pub strategy: Pubkey,
+}
+
+impl StrategyAccount {
+    pub const LEN: usize = 32; // strategy (Pubkey)
+}
+
+#[account]
+pub struct AdapterEntry {
+    pub program: Pubkey,
+}
+
+impl AdapterEntry {
+    pub const LEN: usize = 32; // program (Pubkey)
+}
+
+#[account]
+pub struct RouterEntry {
+    pub program: Pubkey,
+}
+
+impl RouterEntry {
+    pub const LEN: usize = 32; // program (Pubkey)
+}
+
+#[account]
+pub struct QuoteEntry {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub decimals: u8,
+    pub haircut_bps: u16,
+    pub enabled: bool,
+    pub balance: u64,
+}
+
+impl QuoteEntry {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // mint (Pubkey)
+        + 32 // token_account (Pubkey)
+        + 1 // decimals (u8)
+        + 2 // haircut_bps (u16)
+        + 1 // enabled (bool)
+        + 8; // balance (u64)
+
+    // Value of the quote tokens held, in underlying base units after the haircut
+    pub fn value(&self, underlying_decimals: u8) -> u64 {
+        let amount = rescale(self.balance, self.decimals, underlying_decimals);
+        amount.checked_sub(bps_of(amount, self.haircut_bps)).unwrap()
+    }
+}
+
+// A share mint over the vault's pool with its own fee and lockup terms.
+// Class shares are priced like the primary mint and counted in
+// `total_assets`; `supply` is this class's part of it
+#[account]
+pub struct ShareClass {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_fee_bps: u16,
+    pub redeem_fee_bps: u16,
+    pub lockup_slots: u64,
+    pub supply: u64,
+}
+
+impl ShareClass {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // mint (Pubkey)
+        + 2 // deposit_fee_bps (u16)
+        + 2 // redeem_fee_bps (u16)
+        + 8 // lockup_slots (u64)
+        + 8; // supply (u64)
+}
+
+#[account]
+pub struct Basket {
+    pub vault: Pubkey,
+    pub band_bps: u16,
+    pub components: Vec<BasketComponent>,
+}
+
+impl Basket {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 2 // band_bps (u16)
+        + 4 + MAX_BASKET_COMPONENTS * BasketComponent::LEN; // components (Vec<BasketComponent>)
+
+    // How far a component sits from its target weight of `nav`, in signed
+    // basis points; positive when overweight
+    pub fn drift_bps(&self, component: &BasketComponent, nav: u64) -> i64 {
+        if nav == 0 {
+            return 0;
+        }
+        let weight = (component.value() as u128)
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .unwrap()
+            .checked_div(nav as u128)
+            .unwrap() as i64;
+        weight - component.target_weight_bps as i64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BasketComponent {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub oracle: Pubkey,
+    pub decimals: u8,
+    pub target_weight_bps: u16,
+    pub balance: u64,
+    pub price: u64,
+}
+
+impl BasketComponent {
+    pub const LEN: usize = 32 // mint (Pubkey)
+        + 32 // token_account (Pubkey)
+        + 32 // oracle (Pubkey)
+        + 1 // decimals (u8)
+        + 2 // target_weight_bps (u16)
+        + 8 // balance (u64)
+        + 8; // price (u64)
+
+    // USDC value of the balance at the last marked price
+    pub fn value(&self) -> u64 {
+        (self.balance as u128)
+            .checked_mul(self.price as u128)
+            .unwrap()
+            .checked_div(10u128.pow(self.decimals as u32))
+            .unwrap() as u64
+    }
+}
+
+#[account]
+pub struct Strategy {
+    pub vault: Pubkey,
+    pub adapter_program: Pubkey,
+    pub current_debt: u64,
+    pub total_value: u64,
+    pub unrealized_gain: u64,
+    pub max_debt: u64,
+    pub debt_ratio_bps: u16,
+    pub token_accounts: [Pubkey; MAX_STRATEGY_ACCOUNTS],
+}
+
+impl Strategy {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // adapter_program (Pubkey)
+        + 8 // current_debt (u64)
+        + 8 // total_value (u64)
+        + 8 // unrealized_gain (u64)
+        + 8 // max_debt (u64)
+        + 2 // debt_ratio_bps (u16)
+        + 32 * MAX_STRATEGY_ACCOUNTS; // token_accounts ([Pubkey; MAX_STRATEGY_ACCOUNTS])
+
+    // Adapters take whatever token accounts they're handed, so any the vault
+    // signer owns must be ones registered to the strategy
+    pub fn check_accounts<'info>(
+        &self,
+        vault_signer: Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        for account in accounts {
+            if let Ok(token) = InterfaceAccount::<token_interface::TokenAccount>::try_from(account) {
+                require!(
+                    token.owner != vault_signer || self.token_accounts.contains(account.key),
+                    ErrorCode::InvalidStrategyAccount
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Value above cost counts towards backing until it is realized; newly
+    // marked gains are locked and falling marks release locked profit
+    pub fn mark(&mut self, vault: &mut Vault, value: u64, now: i64) {
+        let gain = value.saturating_sub(self.current_debt);
+        if gain > self.unrealized_gain {
+            vault.lock_profit(gain - self.unrealized_gain, now);
+        } else {
+            vault.release_locked_profit(self.unrealized_gain - gain, now);
+        }
+        vault.strategy_gain = vault
+            .strategy_gain
+            .checked_sub(self.unrealized_gain)
+            .unwrap()
+            .checked_add(gain)
+            .unwrap();
+        self.unrealized_gain = gain;
+        self.total_value = value;
+    }
+
+    // Allocation weights across all strategies can't exceed the vault
+    pub fn set_limits(
+        &mut self,
+        vault: &mut Vault,
+        max_debt: u64,
+        debt_ratio_bps: u16,
+    ) -> Result<()> {
+        let total_debt_ratio_bps = vault
+            .total_debt_ratio_bps
+            .checked_sub(self.debt_ratio_bps)
+            .unwrap()
+            .checked_add(debt_ratio_bps)
+            .unwrap();
+        require!(
+            total_debt_ratio_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidDebtRatio
+        );
+
+        vault.total_debt_ratio_bps = total_debt_ratio_bps;
+        self.max_debt = max_debt;
+        self.debt_ratio_bps = debt_ratio_bps;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct RedeemRequest {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub nonce: u64,
+    pub epoch: u64,
+    pub shares: u64,
+    pub receipt_mint: Pubkey,
+}
+
+impl RedeemRequest {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // owner (Pubkey)
+        + 8 // nonce (u64)
+        + 8 // epoch (u64)
+        + 8 // shares (u64)
+        + 32; // receipt_mint (Pubkey)
+}
+
+#[account]
+pub struct RedeemEpoch {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub shares: u64,
+    pub payout: u64,
+    pub price: u64,
+}
+
+impl RedeemEpoch {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 8 // epoch (u64)
+        + 8 // shares (u64)
+        + 8 // payout (u64)
+        + 8; // price (u64)
+}
+
+#[account]
+pub struct WithdrawalRecord {
+    pub index: u64,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    pub slot: u64,
+    pub reason: String,
+}
+
+impl WithdrawalRecord {
+    pub const LEN: usize = 8 // index (u64)
+        + 8 // amount (u64)
+        + 32 // destination (Pubkey)
+        + 32 // authority (Pubkey)
+        + 8 // slot (u64)
+        + 4 + MAX_REASON_LEN; // reason (String)
+}
+
+#[account]
+pub struct WithdrawalProposal {
+    pub vault: Pubkey,
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub approvers: [Pubkey; MAX_AUTHORITIES + 1],
+    pub eta: i64,
+}
+
+impl WithdrawalProposal {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 8 // nonce (u64)
+        + 32 // proposer (Pubkey)
+        + 32 // destination (Pubkey)
+        + 8 // amount (u64)
+        + 32 * (MAX_AUTHORITIES + 1) // approvers ([Pubkey; MAX_AUTHORITIES + 1])
+        + 8; // eta (i64)
+}
+
+#[account]
+pub struct Position {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub deposited: u64,
+    pub last_deposit_slot: u64,
+    pub redeemed: u64,
+    pub share_high_water_mark: u64,
+    pub first_activity_slot: u64,
+    pub last_activity_slot: u64,
+    pub twab_balance: u64,
+    pub twab_cumulative: u128,
+    pub twab_ts: i64,
+}
+
+impl Position {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // owner (Pubkey)
+        + 8 // deposited (u64)
+        + 8 // last_deposit_slot (u64)
+        + 8 // redeemed (u64)
+        + 8 // share_high_water_mark (u64)
+        + 8 // first_activity_slot (u64)
+        + 8 // last_activity_slot (u64)
+        + 8 // twab_balance (u64)
+        + 16 // twab_cumulative (u128)
+        + 8; // twab_ts (i64)
+
+    // Accumulate balance-seconds at the last observed balance, then record the
+    // new one; the average over a window is the cumulative delta over its length
+    pub fn observe_balance(&mut self, balance: u64, now: i64) {
+        let elapsed = now.saturating_sub(self.twab_ts).max(0) as u128;
+        self.twab_cumulative = self
+            .twab_cumulative
+            .checked_add((self.twab_balance as u128).checked_mul(elapsed).unwrap())
+            .unwrap();
+        self.twab_balance = balance;
+        self.twab_ts = now;
+        self.share_high_water_mark = self.share_high_water_mark.max(balance);
+    }
+
+    pub fn record_activity(&mut self, slot: u64) {
+        if self.first_activity_slot == 0 {
+            self.first_activity_slot = slot;
+        }
+        self.last_activity_slot = slot;
+    }
+}
+
+#[account]
+pub struct RewardPool {
+    pub vault: Pubkey,
+    pub reward_per_share: u128,
+    pub total_staked: u64,
+    pub total_boosted: u64,
+    pub total_distributed: u64,
+    pub reward_mint: Pubkey,
+    pub emission_rate: u64,
+    pub emission_per_share: u128,
+    pub emission_ts: i64,
+    pub emissions_remaining: u64,
+    pub bump: u8,
+}
+
+impl RewardPool {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 16 // reward_per_share (u128)
+        + 8 // total_staked (u64)
+        + 8 // total_boosted (u64)
+        + 8 // total_distributed (u64)
+        + 32 // reward_mint (Pubkey)
+        + 8 // emission_rate (u64)
+        + 16 // emission_per_share (u128)
+        + 8 // emission_ts (i64)
+        + 8 // emissions_remaining (u64)
+        + 1; // bump (u8)
+
+    // Emit rate * elapsed (capped by what is funded) across the boosted stake;
+    // nothing is emitted while no shares are staked
+    pub fn accrue_emissions(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.emission_ts).max(0) as u64;
+        if self.total_boosted > 0 && elapsed > 0 {
+            let emitted = self
+                .emission_rate
+                .saturating_mul(elapsed)
+                .min(self.emissions_remaining);
+            self.emission_per_share = self
+                .emission_per_share
+                .checked_add(
+                    (emitted as u128)
+                        .checked_mul(REWARD_INDEX_SCALE)
+                        .unwrap()
+                        .checked_div(self.total_boosted as u128)
+                        .unwrap(),
+                )
+                .unwrap();
+            self.emissions_remaining = self.emissions_remaining.checked_sub(emitted).unwrap();
+        }
+        self.emission_ts = now;
+    }
+}
+
+#[account]
+pub struct YieldCheckpoint {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub staked: u64,
+    pub reward_per_share_paid: u128,
+    pub accrued: u64,
+    pub emission_per_share_paid: u128,
+    pub rewards_accrued: u64,
+    pub lock_tier: LockTier,
+    pub lock_end: i64,
+    pub boosted: u64,
+}
+
+impl YieldCheckpoint {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // owner (Pubkey)
+        + 8 // staked (u64)
+        + 16 // reward_per_share_paid (u128)
+        + 8 // accrued (u64)
+        + 16 // emission_per_share_paid (u128)
+        + 8 // rewards_accrued (u64)
+        + 1 // lock_tier (LockTier)
+        + 8 // lock_end (i64)
+        + 8; // boosted (u64)
+
+    // Credit what the staked shares earned since the last checkpoint, in both
+    // USDC yield and reward token emissions
+    pub fn settle(&mut self, pool: &RewardPool) {
+        let earned = (self.staked as u128)
+            .checked_mul(pool.reward_per_share.checked_sub(self.reward_per_share_paid).unwrap())
+            .unwrap()
+            .checked_div(REWARD_INDEX_SCALE)
+            .unwrap() as u64;
+        self.accrued = self.accrued.checked_add(earned).unwrap();
+        self.reward_per_share_paid = pool.reward_per_share;
+
+        let emitted = (self.boosted as u128)
+            .checked_mul(pool.emission_per_share.checked_sub(self.emission_per_share_paid).unwrap())
+            .unwrap()
+            .checked_div(REWARD_INDEX_SCALE)
+            .unwrap() as u64;
+        self.rewards_accrued = self.rewards_accrued.checked_add(emitted).unwrap();
+        self.emission_per_share_paid = pool.emission_per_share;
+    }
+
+    // Recompute the emission weight after the stake or lock changed; must run
+    // after `settle` so earlier emissions are credited at the old weight
+    pub fn update_boost(&mut self, pool: &mut RewardPool, now: i64) {
+        if now >= self.lock_end {
+            self.lock_tier = LockTier::None;
+        }
+        let boosted = bps_of(self.staked, self.lock_tier.boost_bps());
+        pool.total_boosted = pool
+            .total_boosted
+            .checked_sub(self.boosted)
+            .unwrap()
+            .checked_add(boosted)
+            .unwrap();
+        self.boosted = boosted;
+    }
+}
+
+#[account]
+pub struct Distribution {
+    pub vault: Pubkey,
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(
-        seeds = [b"asset", asset.mint.as_ref()],
-        bump,
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", asset.mint.as_ref()],
-        bump,
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(mut)]
-    pub asset_mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub user_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub vault_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_asset_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+impl Distribution {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 8 // nonce (u64)
+        + 32 // mint (Pubkey)
+        + 32 // merkle_root ([u8; 32])
+        + 8 // total_amount (u64)
+        + 8 // claimed_amount (u64)
+        + 1; // bump (u8)
+
+    // Leaves are keccak(wallet || amount)
+    pub fn contains(&self, wallet: Pubkey, amount: u64, proof: &[[u8; 32]]) -> bool {
+        let leaf = keccak::hashv(&[wallet.as_ref(), &amount.to_le_bytes()]).to_bytes();
+        verify_merkle_proof(self.merkle_root, leaf, proof)
+    }
 }
 
-#[derive(Accounts)]
-pub struct Redeem<'info> {
-    #[account(
-        seeds = [b"asset", asset.mint.as_ref()],
-        bump,
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", asset.mint.as_ref()],
-        bump,
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(mut)]
-    pub asset_mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub user_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub vault_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_asset_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+#[account]
+pub struct DistributionClaim {
+    pub distribution: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
 }
 
-#[derive(Accounts)]
-pub struct AdminWithdraw<'info> {
-    #[account(
-        seeds = [b"admin"],
-        bump,
-    )]
-    pub admin: Account<'info, Admin>,
-    
-    #[account(
-        seeds = [b"asset", asset.mint.as_ref()],
-        bump,
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", asset.mint.as_ref()],
-        bump,
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(mut)]
-    pub vault_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub admin_usdc_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+impl DistributionClaim {
+    pub const LEN: usize = 32 + 32 + 8; // distribution (Pubkey) + wallet (Pubkey) + amount (u64)
 }
 
 #[account]
-pub struct Admin {
-    pub authority: Pubkey,
+pub struct Gauge {
+    pub vault: Pubkey,
+    pub weight: u64,
+    pub bump: u8,
 }
 
-impl Admin {
-    pub const LEN: usize = 32; // authority (Pubkey)
+impl Gauge {
+    pub const LEN: usize = 32 + 8 + 1; // vault (Pubkey) + weight (u64) + bump (u8)
 }
 
 #[account]
-pub struct Asset {
+pub struct VeLock {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lock_end: i64,
+    pub voted_vault: Pubkey,
+    pub vote_weight: u64,
+    pub bump: u8,
+}
+
+impl VeLock {
+    pub const LEN: usize = 32 // vault (Pubkey)
+        + 32 // owner (Pubkey)
+        + 8 // amount (u64)
+        + 8 // lock_end (i64)
+        + 32 // voted_vault (Pubkey)
+        + 8 // vote_weight (u64)
+        + 1; // bump (u8)
+
+    pub fn voting_power(&self, now: i64) -> u64 {
+        let remaining = self.lock_end.saturating_sub(now).max(0);
+        (self.amount as u128)
+            .checked_mul(remaining as u128)
+            .unwrap()
+            .checked_div(MAX_VE_LOCK_DURATION as u128)
+            .unwrap() as u64
+    }
+
+    // Replace this lock's vote on `gauge` with `weight`, keeping the gauge and
+    // global totals in step
+    pub fn set_vote(&mut self, gauge: &mut Gauge, config: &mut Config, weight: u64) {
+        gauge.weight = gauge
+            .weight
+            .checked_sub(self.vote_weight)
+            .unwrap()
+            .checked_add(weight)
+            .unwrap();
+        config.gauge_total_weight = config
+            .gauge_total_weight
+            .checked_sub(self.vote_weight)
+            .unwrap()
+            .checked_add(weight)
+            .unwrap();
+        self.vote_weight = weight;
+        self.voted_vault = if weight > 0 { gauge.vault } else { Pubkey::default() };
+    }
+}
+
+#[account]
+pub struct AllowlistEntry {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = 32 + 32; // vault (Pubkey) + wallet (Pubkey)
+}
+
+#[account]
+pub struct Blacklist {
+    pub wallet: Pubkey,
+}
+
+impl Blacklist {
+    pub const LEN: usize = 32; // wallet (Pubkey)
+}
+
+#[account]
+pub struct Referrer {
+    pub wallet: Pubkey,
+    pub lifetime_volume: u64,
+    pub lifetime_rewards: u64,
+}
+
+impl Referrer {
+    pub const LEN: usize = 32 + 8 + 8; // wallet (Pubkey) + lifetime_volume (u64) + lifetime_rewards (u64)
+}
+
+// Per-asset arguments to batch_create_assets, mirroring create_asset
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AssetParams {
+    pub vault_id: u64,
     pub name: String,
     pub ticker: String,
     pub price: u64,
+    pub deposit_limit: u64,
+    pub share_config: ShareMintConfig,
+    pub category: AssetCategory,
+    pub tags: u16,
+}
+
+// Decimals and Token-2022 extensions for a new share mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ShareMintConfig {
+    pub decimals: u8,
+    pub confidential: bool,
+    pub auto_approve: bool,
+    pub auditor: Option<[u8; 32]>,
+    pub transfer_hook: bool,
+    pub non_transferable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AssetCategory {
+    Stock,
+    Commodity,
+    Fx,
+    Crypto,
+    Treasury,
+}
+
+// Which rate a VaultFeeUpdated event reports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VaultFee {
+    Referral,
+    Performance,
+}
+
+// Which limit a VaultLimitUpdated event reports; the withdraw cap is in bps
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VaultLimit {
+    PerUserCap,
+    MinDeposit,
+    WithdrawCap,
+    RedeemRateLimit,
+    InflowCap,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeMode {
+    // Fees are taken out of USDC liquidity
+    Assets,
+    // Fees are minted as vault shares to the fee recipient
+    Shares,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LockTier {
+    None,
+    Days30,
+    Days90,
+    Days180,
+}
+
+impl LockTier {
+    pub fn duration(&self) -> i64 {
+        match self {
+            LockTier::None => 0,
+            LockTier::Days30 => 30 * 86_400,
+            LockTier::Days90 => 90 * 86_400,
+            LockTier::Days180 => 180 * 86_400,
+        }
+    }
+
+    // Emission weight of a locked share
+    pub fn boost_bps(&self) -> u16 {
+        match self {
+            LockTier::None => 10_000,
+            LockTier::Days30 => 11_000,
+            LockTier::Days90 => 12_500,
+            LockTier::Days180 => 15_000,
+        }
+    }
+}
+
+#[event]
+pub struct DepositLimitUpdated {
+    pub vault: Pubkey,
+    pub old_limit: u64,
+    pub new_limit: u64,
+}
+
+#[event]
+pub struct BufferRebalanced {
+    pub vault: Pubkey,
+    pub target: u64,
+    pub liquid: u64,
+    pub shortfall: u64,
+    pub withdrawn: u64,
+}
+
+#[event]
+pub struct Harvest {
+    pub vault: Pubkey,
+    pub strategy: Pubkey,
+    pub gross_gain: u64,
+    pub fee: u64,
+    pub net_gain: u64,
+}
+
+#[event]
+pub struct StrategyLoss {
+    pub vault: Pubkey,
+    pub strategy: Pubkey,
+    pub loss: u64,
+    pub total_backing: u64,
+}
+
+#[event]
+pub struct AutomationThreadUpdated {
+    pub vault: Pubkey,
+    pub thread: Pubkey,
+}
+
+#[event]
+pub struct Compounded {
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount_in: u64,
+    pub usdc_out: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct StakeUpdated {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub staked: u64,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct StakeLocked {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub tier: LockTier,
+    pub lock_end: i64,
+    pub boosted: u64,
+}
+
+#[event]
+pub struct YieldDistributed {
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub reward_per_share: u128,
+}
+
+#[event]
+pub struct EmissionsFunded {
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub emission_rate: u64,
+    pub emissions_remaining: u64,
+}
+
+#[event]
+pub struct SharesLocked {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lock_end: i64,
+}
+
+#[event]
+pub struct GaugeVoted {
+    pub gauge_vault: Pubkey,
+    pub owner: Pubkey,
+    pub weight: u64,
+    pub gauge_weight: u64,
+}
+
+#[event]
+pub struct GaugeApplied {
+    pub vault: Pubkey,
+    pub weight: u64,
+    pub total_weight: u64,
+    pub emission_rate: u64,
+}
+
+#[event]
+pub struct DistributionCreated {
+    pub vault: Pubkey,
+    pub distribution: Pubkey,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+}
+
+#[event]
+pub struct DistributionClaimed {
+    pub distribution: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HolderFrozen {
+    pub vault: Pubkey,
+    pub share_account: Pubkey,
+    pub owner: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct HolderThawed {
+    pub vault: Pubkey,
+    pub share_account: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct DonationDetected {
+    pub vault: Pubkey,
+    pub expected: u64,
+    pub balance: u64,
+    pub surplus: u64,
+    pub swept: bool,
+}
+
+#[event]
+pub struct TokensSwept {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct StrategyRebalanced {
+    pub vault: Pubkey,
+    pub from_strategy: Pubkey,
+    pub to_strategy: Pubkey,
+    pub withdrawn: u64,
+    pub deployed: u64,
+}
+
+#[event]
+pub struct BasketMarked {
+    pub vault: Pubkey,
+    pub basket_value: u64,
+    pub price: u64,
+}
+
+#[event]
+pub struct BasketRebalanced {
+    pub vault: Pubkey,
     pub mint: Pubkey,
+    pub buy: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[event]
+pub struct MetaPriceSynced {
     pub vault: Pubkey,
-    pub authority: Pubkey,
+    pub child_vault: Pubkey,
+    pub price: u64,
+    pub child_price: u64,
+    pub composed_price: u64,
 }
 
-impl Asset {
-    pub const LEN: usize = 50 + 10 + 8 + 32 + 32 + 32; // name (String) + ticker (String) + price (u64) + mint (Pubkey) + vault (Pubkey) + authority (Pubkey)
+#[event]
+pub struct AssetsSwapped {
+    pub user: Pubkey,
+    pub from_vault: Pubkey,
+    pub to_vault: Pubkey,
+    pub shares_in: u64,
+    pub usdc_moved: u64,
+    pub shares_out: u64,
 }
 
-#[account]
-pub struct Vault {
-    pub total_usdc: u64,
-    pub total_assets: u64,
-    pub deposit_limit: u64,
+#[event]
+pub struct BatchDeposited {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub legs: u8,
 }
 
-impl Vault {
-    pub const LEN: usize = 8 + 8 + 8; // total_usdc (u64) + total_assets (u64) + deposit_limit (u64)
+#[event]
+pub struct SwapDeposited {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub amount_in: u64,
+    pub usdc_out: u64,
+}
+
+#[event]
+pub struct SwapRedeemed {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub output_mint: Pubkey,
+    pub usdc_in: u64,
+    pub amount_out: u64,
+}
+
+#[event]
+pub struct QuoteDeposited {
+    pub vault: Pubkey,
+    pub quote_mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub value: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct QuoteConverted {
+    pub vault: Pubkey,
+    pub quote_mint: Pubkey,
+    pub amount_in: u64,
+    pub usdc_out: u64,
+    pub released: u64,
+}
+
+#[event]
+pub struct DepositEvent {
+    pub user: Pubkey,
+    pub asset: Pubkey,
+    pub usdc_in: u64,
+    pub shares_out: u64,
+    pub price: u64,
+    pub memo: Option<String>,
+}
+
+#[event]
+pub struct RedeemEvent {
+    pub user: Pubkey,
+    pub asset: Pubkey,
+    pub shares_in: u64,
+    pub usdc_out: u64,
+    pub price: u64,
+    pub memo: Option<String>,
+}
+
+#[event]
+pub struct AssetVerificationSet {
+    pub asset: Pubkey,
+    pub verified: bool,
+}
+
+#[event]
+pub struct BondSlashed {
+    pub asset: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct AdminWithdrawal {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub old_debt: u64,
+    pub new_debt: u64,
+}
+
+#[event]
+pub struct PriceUpdated {
+    pub asset: Pubkey,
+    pub old_price: u64,
+    pub new_price: u64,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub vault: Pubkey,
+    pub old_deposit_fee_bps: u16,
+    pub new_deposit_fee_bps: u16,
+    pub old_redeem_fee_bps: u16,
+    pub new_redeem_fee_bps: u16,
+    pub old_fee_recipient: Pubkey,
+    pub new_fee_recipient: Pubkey,
+    pub old_fee_mode: FeeMode,
+    pub new_fee_mode: FeeMode,
+}
+
+#[event]
+pub struct VaultFeeUpdated {
+    pub vault: Pubkey,
+    pub kind: VaultFee,
+    pub old_bps: u16,
+    pub new_bps: u16,
+}
+
+#[event]
+pub struct CreationFeeUpdated {
+    pub old_fee: u64,
+    pub new_fee: u64,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct VaultLimitUpdated {
+    pub vault: Pubkey,
+    pub kind: VaultLimit,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct VaultPauseUpdated {
+    pub vault: Pubkey,
+    pub old_flags: u8,
+    pub new_flags: u8,
+}
+
+#[event]
+pub struct GlobalPauseUpdated {
+    pub old_paused: bool,
+    pub new_paused: bool,
+}
+
+#[event]
+pub struct AdminAuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AssetAuthorityTransferred {
+    pub asset: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
 }
 
 #[error_code]
@@ -364,4 +13916,252 @@ pub enum ErrorCode {
     Unauthorized,
     #[msg("Deposit would exceed limit")]
     DepositLimitExceeded,
+    #[msg("No signer rotation is scheduled")]
+    RotationNotScheduled,
+    #[msg("Signer rotation timelock has not elapsed")]
+    RotationTimelockActive,
+    #[msg("Vault signer must be migrated to the current version")]
+    VaultSignerOutdated,
+    #[msg("Vault signer is already on the current version")]
+    VaultSignerCurrent,
+    #[msg("Accounts owned by the old vault signer must be emptied or migrated first")]
+    SignerAccountsNotMigrated,
+    #[msg("Fee exceeds the maximum allowed")]
+    FeeTooHigh,
+    #[msg("No fees to collect")]
+    NoFeesToCollect,
+    #[msg("Token account is not owned by the fee recipient")]
+    InvalidFeeRecipient,
+    #[msg("Asset mint and fee recipient asset account are required to mint fee shares")]
+    MissingFeeShareAccounts,
+    #[msg("Depositor cannot refer themselves")]
+    SelfReferral,
+    #[msg("Referrer USDC account is missing or not owned by the referrer")]
+    MissingReferrerAccount,
+    #[msg("Treasury does not match the protocol config")]
+    InvalidTreasury,
+    #[msg("Deposits are paused for this vault")]
+    DepositsPaused,
+    #[msg("Redemptions are paused for this vault")]
+    RedemptionsPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Authority is already an admin")]
+    AuthorityAlreadyExists,
+    #[msg("No free admin authority slots")]
+    AuthorityListFull,
+    #[msg("Authority is not an admin")]
+    AuthorityNotFound,
+    #[msg("Withdrawal must go through the approval flow")]
+    WithdrawalRequiresApproval,
+    #[msg("Threshold must be between one and the number of admins")]
+    InvalidThreshold,
+    #[msg("Authority has already approved this withdrawal")]
+    AlreadyApproved,
+    #[msg("Not enough admin approvals")]
+    InsufficientApprovals,
+    #[msg("Change must be queued through the timelock")]
+    TimelockRequired,
+    #[msg("Timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Timelock delay cannot be negative")]
+    InvalidTimelockDelay,
+    #[msg("Price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Accounts do not match the queued action")]
+    InvalidActionTarget,
+    #[msg("Withdraw cap must not exceed 100%")]
+    InvalidWithdrawCap,
+    #[msg("Admin withdrawals would exceed the cap for this epoch")]
+    WithdrawCapExceeded,
+    #[msg("Repayment exceeds outstanding admin debt")]
+    RepayExceedsDebt,
+    #[msg("Withdrawal would leave the vault under-collateralized")]
+    InsufficientCollateral,
+    #[msg("Reason is too long")]
+    ReasonTooLong,
+    #[msg("Governance account is not owned by the governance program")]
+    InvalidGovernance,
+    #[msg("Vault has been shut down")]
+    VaultShutdown,
+    #[msg("Asset has been delisted")]
+    AssetDelisted,
+    #[msg("Settlement price and window must be greater than zero")]
+    InvalidSettlement,
+    #[msg("Settlement redemption window has closed")]
+    SettlementWindowClosed,
+    #[msg("Vault still holds funds or outstanding shares")]
+    VaultNotEmpty,
+    #[msg("URI is too long")]
+    UriTooLong,
+    #[msg("Deposit would exceed the per-user cap")]
+    PerUserCapExceeded,
+    #[msg("Deposit is below the minimum amount")]
+    DepositTooSmall,
+    #[msg("First deposit into a vault is below the bootstrap minimum")]
+    InitialDepositTooSmall,
+    #[msg("Depositor is not on the vault allowlist")]
+    NotAllowlisted,
+    #[msg("Wallet is blacklisted")]
+    Blacklisted,
+    #[msg("Wallet is not blacklisted")]
+    NotBlacklisted,
+    #[msg("Depositor does not hold a valid credential")]
+    MissingCredential,
+    #[msg("Redeem cooldown exceeds the maximum")]
+    InvalidRedeemCooldown,
+    #[msg("Shares are still within the redeem cooldown")]
+    RedeemCooldownActive,
+    #[msg("Cannot redeem in the same slot as a deposit")]
+    SameSlotRedeem,
+    #[msg("Redeem epoch duration is out of range")]
+    InvalidRedeemEpochDuration,
+    #[msg("Redeem epoch has not ended")]
+    RedeemEpochActive,
+    #[msg("No queued redemptions")]
+    NoPendingRedemptions,
+    #[msg("Redemption request has already been settled")]
+    RedeemAlreadySettled,
+    #[msg("Redemption exceeds the rate limit; use the withdrawal queue")]
+    RedeemRateLimited,
+    #[msg("Inflow window must be at least one slot")]
+    InvalidInflowWindow,
+    #[msg("Deposit would exceed the inflow cap for this window")]
+    InflowCapExceeded,
+    #[msg("Buffer target must not exceed 100%")]
+    InvalidBufferTarget,
+    #[msg("Withdrawal would drain the vault's liquidity buffer")]
+    BufferTargetBreached,
+    #[msg("Strategy adapter is not whitelisted")]
+    AdapterNotWhitelisted,
+    #[msg("Token account is not registered to the strategy")]
+    InvalidStrategyAccount,
+    #[msg("Adapter moved more than requested")]
+    AdapterAmountMismatch,
+    #[msg("Adapter did not return a valid value")]
+    InvalidAdapterValue,
+    #[msg("Strategy debt ratios exceed 100%")]
+    InvalidDebtRatio,
+    #[msg("Deploy exceeds the strategy's debt limit")]
+    StrategyDebtLimitExceeded,
+    #[msg("Loss must be positive and within the strategy's debt")]
+    InvalidLoss,
+    #[msg("Profit unlock period is out of range")]
+    InvalidProfitUnlockPeriod,
+    #[msg("Rebalance cap must not exceed 100%")]
+    InvalidRebalanceCap,
+    #[msg("Nothing to move between these strategies")]
+    InvalidRebalance,
+    #[msg("Automation thread must be set")]
+    InvalidAutomationThread,
+    #[msg("Router is not whitelisted")]
+    RouterNotWhitelisted,
+    #[msg("Router accounts touch vault funds other than the swap")]
+    InvalidRouterAccounts,
+    #[msg("Reward account cannot hold USDC or vault shares")]
+    InvalidRewardAccount,
+    #[msg("Swap returned less than the minimum")]
+    SlippageExceeded,
+    #[msg("Stake amount must be greater than zero")]
+    InvalidStakeAmount,
+    #[msg("Not enough shares staked")]
+    InsufficientStake,
+    #[msg("No shares are staked in the reward pool")]
+    NoStakedShares,
+    #[msg("Amount exceeds the vault's distributable yield")]
+    InsufficientYield,
+    #[msg("No yield to claim")]
+    NothingToClaim,
+    #[msg("Vault balance matches its accounting")]
+    NothingToSync,
+    #[msg("Treasury USDC account is required to sweep donations")]
+    MissingTreasuryAccount,
+    #[msg("The vault's USDC, shares and strategy positions cannot be swept")]
+    CannotSweepVaultToken,
+    #[msg("Nothing to sweep")]
+    NothingToSweep,
+    #[msg("Deposit is too small to mint any shares")]
+    ZeroShares,
+    #[msg("Redemption would leave a balance below the dust threshold")]
+    DustRemainder,
+    #[msg("No shares to redeem")]
+    NoSharesToRedeem,
+    #[msg("Account still holds a balance")]
+    PositionNotEmpty,
+    #[msg("Position backs the per-user deposit cap")]
+    PositionInUse,
+    #[msg("A receipt has already been minted for this request")]
+    ReceiptAlreadyMinted,
+    #[msg("Request is held by its receipt")]
+    ReceiptOutstanding,
+    #[msg("Invalid lock tier")]
+    InvalidLockTier,
+    #[msg("Stake is still locked")]
+    StakeStillLocked,
+    #[msg("Lock duration is out of range")]
+    InvalidLockDuration,
+    #[msg("Lock has an active gauge vote")]
+    VoteActive,
+    #[msg("Lock has no voting power left")]
+    NoVotingPower,
+    #[msg("Emissions are already initialized for this reward pool")]
+    EmissionsAlreadyInitialized,
+    #[msg("Distribution needs a merkle root and a non-zero amount")]
+    InvalidDistribution,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Distribution has been fully claimed")]
+    DistributionExhausted,
+    #[msg("Transfer hook invoked outside of a token transfer")]
+    NotTransferring,
+    #[msg("Share mint extensions require the Token-2022 program")]
+    Token2022Required,
+    #[msg("Non-transferable shares can't use confidential transfers or a transfer hook")]
+    InvalidShareConfig,
+    #[msg("Quote mint must differ from the vault's underlying mint")]
+    InvalidQuoteMint,
+    #[msg("Haircut is too high")]
+    InvalidHaircut,
+    #[msg("Deposits in this quote mint are disabled")]
+    QuoteDisabled,
+    #[msg("Vault doesn't hold that much of the quote mint")]
+    InsufficientQuoteBalance,
+    #[msg("Share decimals must be 6, 8 or 9")]
+    InvalidShareDecimals,
+    #[msg("Vault's underlying is not wrapped SOL")]
+    NotNativeVault,
+    #[msg("User's underlying token account is required")]
+    MissingUnderlyingAccount,
+    #[msg("Swap input or output must differ from the vault's underlying")]
+    InvalidSwapMint,
+    #[msg("Child vault's shares can't back this vault")]
+    InvalidChildVault,
+    #[msg("Invalid basket configuration")]
+    InvalidBasket,
+    #[msg("Basket component is already within its band")]
+    BasketWithinBand,
+    #[msg("Oracle account is not a valid price feed")]
+    InvalidOracle,
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+    #[msg("Batch weights or accounts are invalid")]
+    InvalidBatch,
+    #[msg("Vault restricts who can deposit and can't be part of a batch")]
+    BatchLegRestricted,
+    #[msg("Share class lockup has not elapsed")]
+    ShareClassLocked,
+    #[msg("Registry account does not match the config")]
+    InvalidRegistry,
+    #[msg("Registry is full")]
+    RegistryFull,
+    #[msg("Ticker must be letters, digits, '.' or '-'")]
+    InvalidTicker,
+    #[msg("Ticker can't be changed once claimed")]
+    TickerImmutable,
+    #[msg("Memo is too long")]
+    MemoTooLong,
 }
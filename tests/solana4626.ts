@@ -1,14 +1,23 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Solana4626 } from "../target/types/solana4626";
-import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createMint,
   createAccount,
+  createAssociatedTokenAccount,
+  createTransferCheckedWithTransferHookInstruction,
   mintTo,
   getAccount,
+  getMint,
 } from "@solana/spl-token";
 import { assert } from "chai";
 
@@ -20,6 +29,7 @@ describe("solana4626", () => {
   
   let admin: PublicKey;
   let adminBump: number;
+  let config: PublicKey;
   let usdcMint: PublicKey;
   let assetMint: PublicKey;
   let userUsdcAccount: PublicKey;
@@ -30,6 +40,11 @@ describe("solana4626", () => {
   let assetBump: number;
   let vault: PublicKey;
   let vaultBump: number;
+  let vaultSigner: PublicKey;
+  let blacklistEntry: PublicKey;
+  let registry: PublicKey;
+  let stats: PublicKey;
+  let position: PublicKey;
 
   before(async () => {
     // Find admin PDA
@@ -38,6 +53,18 @@ describe("solana4626", () => {
       program.programId
     );
 
+    // Find config PDA
+    [config] = await PublicKey.findProgramAddress(
+      [Buffer.from("config")],
+      program.programId
+    );
+
+    // Blacklist entry for the test user; stays empty unless blacklisted
+    [blacklistEntry] = await PublicKey.findProgramAddress(
+      [Buffer.from("blacklist"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
     // Create USDC mint (for testing)
     usdcMint = await createMint(
       provider.connection,
@@ -47,15 +74,6 @@ describe("solana4626", () => {
       6 // USDC has 6 decimals
     );
 
-    // Create asset mint
-    assetMint = await createMint(
-      provider.connection,
-      provider.wallet.payer,
-      provider.wallet.publicKey,
-      null,
-      9
-    );
-
     // Create user USDC account
     userUsdcAccount = await createAccount(
       provider.connection,
@@ -82,15 +100,47 @@ describe("solana4626", () => {
       1_000_000_000 // 1000 USDC
     );
 
-    // Find asset PDA
+    // Find asset PDA, the first vault over USDC
+    const vaultId = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
     [asset, assetBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("asset"), assetMint.toBuffer()],
+      [Buffer.from("asset"), usdcMint.toBuffer(), vaultId],
       program.programId
     );
 
     // Find vault PDA
     [vault, vaultBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("vault"), assetMint.toBuffer()],
+      [Buffer.from("vault"), usdcMint.toBuffer(), vaultId],
+      program.programId
+    );
+
+    // Asset mint and vault token accounts are controlled by the vault signer;
+    // the mint itself is created by create_asset at a PDA of the asset
+    [assetMint] = await PublicKey.findProgramAddress(
+      [Buffer.from("share_mint"), asset.toBuffer()],
+      program.programId
+    );
+    [vaultSigner] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("vault_signer"),
+        assetMint.toBuffer(),
+        Buffer.from([0]),
+      ],
+      program.programId
+    );
+
+    // Find stats PDA
+    [stats] = await PublicKey.findProgramAddress(
+      [Buffer.from("stats"), asset.toBuffer()],
+      program.programId
+    );
+
+    // Find the test user's position in the vault
+    [position] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("position"),
+        vault.toBuffer(),
+        provider.wallet.publicKey.toBuffer(),
+      ],
       program.programId
     );
 
@@ -99,16 +149,8 @@ describe("solana4626", () => {
       provider.connection,
       provider.wallet.payer,
       usdcMint,
-      vault,
-      true // allowOwnerOffCurve
-    );
-
-    // Create user asset account
-    userAssetAccount = await createAccount(
-      provider.connection,
-      provider.wallet.payer,
-      assetMint,
-      provider.wallet.publicKey
+      vaultSigner,
+      anchor.web3.Keypair.generate()
     );
   });
 
@@ -126,18 +168,92 @@ describe("solana4626", () => {
     assert.ok(adminAccount.authority.equals(provider.wallet.publicKey));
   });
 
+  it("Initializes the protocol config", async () => {
+    const creationFee = new anchor.BN(10_000_000); // 0.01 SOL
+    const treasury = anchor.web3.Keypair.generate().publicKey;
+
+    await program.methods
+      .initializeConfig(creationFee, treasury)
+      .accounts({
+        admin,
+        config,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const configAccount = await program.account.config.fetch(config);
+    assert.equal(configAccount.creationFee.toNumber(), creationFee.toNumber());
+    assert.ok(configAccount.treasury.equals(treasury));
+  });
+
+  it("Initializes the asset registry", async () => {
+    const registryKeypair = anchor.web3.Keypair.generate();
+    registry = registryKeypair.publicKey;
+    const space = program.account.registry.size;
+
+    await program.methods
+      .initRegistry()
+      .accounts({
+        admin,
+        config,
+        registry,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .preInstructions([
+        SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: registry,
+          space,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId: program.programId,
+        }),
+      ])
+      .signers([registryKeypair])
+      .rpc();
+
+    const configAccount = await program.account.config.fetch(config);
+    assert.ok(configAccount.registry.equals(registry));
+  });
+
   it("Creates a new asset", async () => {
     const name = "Test Asset";
     const ticker = "TEST";
     const price = new anchor.BN(1_000_000); // 1 USDC per asset token
     const depositLimit = new anchor.BN(1_000_000_000); // 1000 USDC deposit limit
+    const { treasury, creationFee } = await program.account.config.fetch(config);
+    const shareConfig = {
+      decimals: 6,
+      confidential: false,
+      autoApprove: false,
+      auditor: null,
+      transferHook: false,
+      nonTransferable: false,
+    };
 
     await program.methods
-      .createAsset(name, ticker, price, depositLimit)
+      .createAsset(new anchor.BN(0), name, ticker, price, depositLimit, shareConfig, { stock: {} }, 0)
       .accounts({
+        admin,
+        config,
+        treasury,
+        registry,
         asset,
         vault,
+        tickerClaim: PublicKey.findProgramAddressSync(
+          [Buffer.from("ticker"), Buffer.from(ticker)],
+          program.programId
+        )[0],
+        bond: PublicKey.findProgramAddressSync(
+          [Buffer.from("bond"), asset.toBuffer()],
+          program.programId
+        )[0],
+        stats,
+        vaultSigner,
         mint: assetMint,
+        underlyingMint: usdcMint,
         authority: provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -152,26 +268,145 @@ describe("solana4626", () => {
     assert.ok(assetAccount.mint.equals(assetMint));
     assert.ok(assetAccount.vault.equals(vault));
     assert.ok(assetAccount.authority.equals(provider.wallet.publicKey));
+    assert.deepEqual(assetAccount.category, { stock: {} });
 
     const vaultAccount = await program.account.vault.fetch(vault);
     assert.equal(vaultAccount.depositLimit.toNumber(), depositLimit.toNumber());
+    assert.ok(vaultAccount.underlyingMint.equals(usdcMint));
+    assert.equal(vaultAccount.underlyingDecimals, 6);
+    assert.equal(vaultAccount.shareDecimals, 6);
+
+    const treasuryBalance = await provider.connection.getBalance(treasury);
+    assert.equal(treasuryBalance, creationFee.toNumber());
+
+    const [entry] = await program.methods
+      .listAssets(new anchor.BN(0), new anchor.BN(10))
+      .accounts({ config, registry })
+      .view();
+    assert.ok(entry.asset.equals(asset));
+    assert.ok(entry.mint.equals(assetMint));
+
+    // Share mint is owned by the vault signer with the underlying's decimals
+    const mintInfo = await getMint(provider.connection, assetMint);
+    assert.ok(mintInfo.mintAuthority.equals(vaultSigner));
+    assert.ok(mintInfo.freezeAuthority.equals(vaultSigner));
+    assert.equal(mintInfo.decimals, 6);
+
+    userAssetAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      assetMint,
+      provider.wallet.publicKey
+    );
+
+    // Deposits and redemptions record on the user's position
+    await program.methods
+      .openPosition()
+      .accounts({
+        asset,
+        vault,
+        position,
+        owner: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("Rejects a ticker that is already claimed", async () => {
+    const { treasury } = await program.account.config.fetch(config);
+    const vaultId = new anchor.BN(1);
+    const [spoofAsset] = PublicKey.findProgramAddressSync(
+      [Buffer.from("asset"), usdcMint.toBuffer(), vaultId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [mint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), spoofAsset.toBuffer()],
+      program.programId
+    );
+    const [spoofVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), usdcMint.toBuffer(), vaultId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [spoofSigner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_signer"), mint.toBuffer(), Buffer.from([0])],
+      program.programId
+    );
+    // " test " normalizes to the "TEST" ticker claimed above
+    const [tickerClaim] = PublicKey.findProgramAddressSync(
+      [Buffer.from("ticker"), Buffer.from("TEST")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .createAsset(vaultId, "Spoof", " test ", new anchor.BN(1_000_000), new anchor.BN(0), {
+          decimals: 6,
+          confidential: false,
+          autoApprove: false,
+          auditor: null,
+          transferHook: false,
+          nonTransferable: false,
+        }, { stock: {} }, 0)
+        .accounts({
+          admin,
+          config,
+          treasury,
+          registry,
+          asset: spoofAsset,
+          vault: spoofVault,
+          tickerClaim,
+          bond: PublicKey.findProgramAddressSync(
+            [Buffer.from("bond"), spoofAsset.toBuffer()],
+            program.programId
+          )[0],
+          stats: PublicKey.findProgramAddressSync(
+            [Buffer.from("stats"), spoofAsset.toBuffer()],
+            program.programId
+          )[0],
+          vaultSigner: spoofSigner,
+          mint,
+          underlyingMint: usdcMint,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      assert.fail("Expected a duplicate ticker to be rejected");
+    } catch (err) {
+      assert.include(err.message, "already in use");
+    }
   });
 
   it("Deposits USDC and receives asset tokens", async () => {
     const depositAmount = new anchor.BN(100_000); // 0.1 USDC
 
     await program.methods
-      .deposit(depositAmount)
+      .deposit(depositAmount, [])
       .accounts({
+        admin,
+        config,
         asset,
         vault,
+        vaultSigner,
+        stats,
         assetMint,
+        usdcMint,
         userUsdcAccount,
         vaultUsdcAccount,
         userAssetAccount,
         user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass: null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
 
@@ -187,17 +422,31 @@ describe("solana4626", () => {
     // First deposit 900 USDC (leaving 100 USDC capacity)
     const firstDeposit = new anchor.BN(900_000_000);
     await program.methods
-      .deposit(firstDeposit)
+      .deposit(firstDeposit, [])
       .accounts({
+        admin,
+        config,
         asset,
         vault,
+        vaultSigner,
+        stats,
         assetMint,
+        usdcMint,
         userUsdcAccount,
         vaultUsdcAccount,
         userAssetAccount,
         user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass: null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
 
@@ -205,17 +454,31 @@ describe("solana4626", () => {
     const secondDeposit = new anchor.BN(200_000_000);
     try {
       await program.methods
-        .deposit(secondDeposit)
+        .deposit(secondDeposit, [])
         .accounts({
+          admin,
+          config,
           asset,
           vault,
+          vaultSigner,
+          stats,
           assetMint,
+          usdcMint,
           userUsdcAccount,
           vaultUsdcAccount,
           userAssetAccount,
           user: provider.wallet.publicKey,
+          referrer: null,
+          referrerUsdcAccount: null,
+          position,
+          allowlistEntry: null,
+          blacklistEntry,
+          credential: null,
+          shareClass: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
         .rpc();
       assert.fail("Expected deposit to fail due to limit");
@@ -231,42 +494,1957 @@ describe("solana4626", () => {
   it("Redeems asset tokens for USDC", async () => {
     const redeemAmount = new anchor.BN(50_000); // 0.05 asset tokens
 
+    // Nothing redeems in the slot of the wallet's last deposit
+    const { lastDepositSlot } = await program.account.position.fetch(position);
+    while ((await provider.connection.getSlot()) <= lastDepositSlot.toNumber()) {}
+
     await program.methods
       .redeem(redeemAmount)
       .accounts({
+        admin,
+        config,
         asset,
         vault,
+        vaultSigner,
+        stats,
         assetMint,
+        usdcMint,
         userUsdcAccount,
         vaultUsdcAccount,
         userAssetAccount,
         user: provider.wallet.publicKey,
+        blacklistEntry,
+        position,
+        shareClass: null,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
 
     const vaultAccount = await program.account.vault.fetch(vault);
     assert.equal(vaultAccount.totalUsdc.toNumber(), 50_000); // 0.05 USDC remaining
     assert.equal(vaultAccount.totalAssets.toNumber(), 50_000); // 0.05 asset tokens remaining
+
+    const statsAccount = await program.account.stats.fetch(stats);
+    assert.equal(statsAccount.redeemCount.toNumber(), 1);
+    assert.equal(statsAccount.redeemVolume.toNumber(), 50_000);
   });
 
-  it("Admin withdraws USDC from vault", async () => {
+  it("Admin borrows USDC from vault", async () => {
     const withdrawAmount = new anchor.BN(25_000); // 0.025 USDC
+    const reason = "Treasury rebalance";
+    const { withdrawalRecordCount } = await program.account.vault.fetch(vault);
+    const [withdrawalRecord] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("withdrawal_record"),
+        vault.toBuffer(),
+        withdrawalRecordCount.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const borrowAccounts = {
+      admin,
+      config,
+      asset,
+      vault,
+      vaultSigner,
+      usdcMint,
+      vaultUsdcAccount,
+      adminUsdcAccount,
+      withdrawalRecord,
+      roleGrant: null,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // Fully collateralized by default, so any borrow is refused
+    try {
+      await program.methods.adminBorrow(withdrawAmount, reason).accounts(borrowAccounts).rpc();
+      assert.fail("Expected borrow to fail while fully collateralized");
+    } catch (err) {
+      assert.include(err.message, "InsufficientCollateral");
+    }
 
     await program.methods
-      .adminWithdraw(withdrawAmount)
+      .setMinCollateral(5_000) // 50%
       .accounts({
         admin,
+        config,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods.adminBorrow(withdrawAmount, reason).accounts(borrowAccounts).rpc();
+
+    const record = await program.account.withdrawalRecord.fetch(withdrawalRecord);
+    assert.equal(record.amount.toNumber(), withdrawAmount.toNumber());
+    assert.ok(record.destination.equals(adminUsdcAccount));
+    assert.equal(record.reason, reason);
+
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.totalUsdc.toNumber(), 25_000); // 0.025 USDC remaining
+    assert.equal(vaultAccount.adminDebt.toNumber(), withdrawAmount.toNumber());
+  });
+
+  it("Admin repays borrowed USDC", async () => {
+    const repayAmount = new anchor.BN(25_000);
+
+    await program.methods
+      .adminRepay(repayAmount)
+      .accounts({
         asset,
         vault,
+        vaultSigner,
+        usdcMint,
         vaultUsdcAccount,
-        adminUsdcAccount,
+        repayerUsdcAccount: adminUsdcAccount,
+        repayer: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.totalUsdc.toNumber(), 50_000);
+    assert.equal(vaultAccount.adminDebt.toNumber(), 0);
+  });
+
+  it("Enforces the timelock on vault signer rotation", async () => {
+    await program.methods
+      .scheduleSignerRotation()
+      .accounts({
+        admin,
+        guardian: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .executeSignerRotation()
+        .accounts({
+          admin,
+          guardian: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("Expected rotation to fail before the timelock elapses");
+    } catch (err) {
+      assert.include(err.message, "RotationTimelockActive");
+    }
+
+    await program.methods
+      .cancelSignerRotation()
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const adminAccount = await program.account.admin.fetch(admin);
+    assert.equal(adminAccount.signerVersion, 0);
+    assert.equal(adminAccount.rotationUnlockTs.toNumber(), 0);
+  });
+
+  it("Accrues deposit fees separately and collects them", async () => {
+    await program.methods
+      .setFees(100, 0, provider.wallet.publicKey, { assets: {} }) // 1% deposit fee
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const before = await program.account.vault.fetch(vault);
+    const depositAmount = new anchor.BN(10_000_000); // 10 USDC
+
+    await program.methods
+      .deposit(depositAmount, [])
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        stats,
+        assetMint,
+        usdcMint,
+        userUsdcAccount,
+        vaultUsdcAccount,
+        userAssetAccount,
+        user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    let vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.feesOutstanding.toNumber(), 100_000);
+    assert.equal(
+      vaultAccount.totalUsdc.sub(before.totalUsdc).toNumber(),
+      9_900_000
+    );
+
+    await program.methods
+      .collectFees()
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        usdcMint,
+        vaultUsdcAccount,
+        feeRecipientUsdcAccount: adminUsdcAccount,
+        assetMint: null,
+        feeRecipientAssetAccount: null,
+        roleGrant: null,
         authority: provider.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.feesOutstanding.toNumber(), 0);
+  });
+
+  it("Registers a referrer", async () => {
+    const wallet = anchor.web3.Keypair.generate();
+    const [referrer] = await PublicKey.findProgramAddress(
+      [Buffer.from("referrer"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(wallet.publicKey, 1_000_000_000)
+    );
+
+    await program.methods
+      .registerReferrer()
+      .accounts({
+        config,
+        referrer,
+        wallet: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([wallet])
+      .rpc();
+
+    const referrerAccount = await program.account.referrer.fetch(referrer);
+    assert.ok(referrerAccount.wallet.equals(wallet.publicKey));
+    assert.equal(referrerAccount.lifetimeVolume.toNumber(), 0);
+  });
+
+  it("Rejects deposits while the vault is paused", async () => {
+    await program.methods
+      .pauseVault(1) // deposits only
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .deposit(new anchor.BN(1_000_000), [])
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          stats,
+          assetMint,
+          usdcMint,
+          userUsdcAccount,
+          vaultUsdcAccount,
+          userAssetAccount,
+          user: provider.wallet.publicKey,
+          referrer: null,
+          referrerUsdcAccount: null,
+          position,
+          allowlistEntry: null,
+          blacklistEntry,
+          credential: null,
+          shareClass: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected deposit to fail while paused");
+    } catch (err) {
+      assert.include(err.message, "DepositsPaused");
+    }
+
+    await program.methods
+      .unpauseVault(1)
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
       })
       .rpc();
 
     const vaultAccount = await program.account.vault.fetch(vault);
-    assert.equal(vaultAccount.totalUsdc.toNumber(), 25_000); // 0.025 USDC remaining
+    assert.equal(vaultAccount.pauseFlags, 0);
+  });
+
+  it("Halts deposits while the protocol is paused", async () => {
+    await program.methods
+      .setGlobalPause(true)
+      .accounts({
+        admin,
+        config,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .deposit(new anchor.BN(1_000_000), [])
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          stats,
+          assetMint,
+          usdcMint,
+          userUsdcAccount,
+          vaultUsdcAccount,
+          userAssetAccount,
+          user: provider.wallet.publicKey,
+          referrer: null,
+          referrerUsdcAccount: null,
+          position,
+          allowlistEntry: null,
+          blacklistEntry,
+          credential: null,
+          shareClass: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected deposit to fail while the protocol is paused");
+    } catch (err) {
+      assert.include(err.message, "ProtocolPaused");
+    }
+
+    await program.methods
+      .setGlobalPause(false)
+      .accounts({
+        admin,
+        config,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Lets the guardian pause but not unpause", async () => {
+    const guardian = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .pauseVault(3)
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: guardian.publicKey,
+      })
+      .signers([guardian])
+      .rpc();
+
+    try {
+      await program.methods
+        .unpauseVault(3)
+        .accounts({
+          admin,
+          asset,
+          vault,
+          roleGrant: null,
+          authority: guardian.publicKey,
+        })
+        .signers([guardian])
+        .rpc();
+      assert.fail("Expected guardian unpause to fail");
+    } catch (err) {
+      assert.include(err.message, "Unauthorized");
+    }
+
+    await program.methods
+      .unpauseVault(3)
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .setGuardian(provider.wallet.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Grants and revokes the pauser role", async () => {
+    const pauser = anchor.web3.Keypair.generate();
+    const [roleGrant] = await PublicKey.findProgramAddress(
+      [Buffer.from("role"), pauser.publicKey.toBuffer(), Buffer.from([3])],
+      program.programId
+    );
+
+    await program.methods
+      .grantRole({ pauser: {} }, pauser.publicKey)
+      .accounts({
+        admin,
+        roleGrant,
+        authorityGrant: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .pauseVault(2) // redemptions only
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant,
+        authority: pauser.publicKey,
+      })
+      .signers([pauser])
+      .rpc();
+
+    let vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.pauseFlags, 2);
+
+    await program.methods
+      .unpauseVault(2)
+      .accounts({
+        admin,
+        asset,
+        vault,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .revokeRole()
+      .accounts({
+        admin,
+        roleGrant,
+        authorityGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(roleGrant));
+  });
+
+  it("Requires the proposed authority to accept the transfer", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .acceptAuthority()
+        .accounts({
+          admin,
+          newAuthority: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("Expected accept from a non-pending signer to fail");
+    } catch (err) {
+      assert.include(err.message, "NotPendingAuthority");
+    }
+
+    await program.methods
+      .acceptAuthority()
+      .accounts({
+        admin,
+        newAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    // Hand control back to the provider wallet
+    await program.methods
+      .proposeAuthority(provider.wallet.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accounts({
+        admin,
+        newAuthority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const adminAccount = await program.account.admin.fetch(admin);
+    assert.ok(adminAccount.authority.equals(provider.wallet.publicKey));
+  });
+
+  it("Adds and removes co-admin authorities", async () => {
+    const coAdmin = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .addAuthority(coAdmin.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    let adminAccount = await program.account.admin.fetch(admin);
+    assert.ok(adminAccount.authorities.some((key) => key.equals(coAdmin.publicKey)));
+
+    await program.methods
+      .removeAuthority(coAdmin.publicKey)
+      .accounts({
+        admin,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    adminAccount = await program.account.admin.fetch(admin);
+    assert.notOk(adminAccount.authorities.some((key) => key.equals(coAdmin.publicKey)));
+  });
+
+  it("Requires M-of-N approval for admin withdrawals", async () => {
+    const coAdmin = anchor.web3.Keypair.generate();
+    const updateAdmin = { admin, roleGrant: null, authority: provider.wallet.publicKey };
+
+    await program.methods.addAuthority(coAdmin.publicKey).accounts(updateAdmin).rpc();
+    await program.methods.setWithdrawThreshold(2).accounts(updateAdmin).rpc();
+
+    const { withdrawalNonce } = await program.account.vault.fetch(vault);
+    const [proposal] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("withdrawal"),
+        vault.toBuffer(),
+        withdrawalNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .proposeWithdraw(new anchor.BN(1_000))
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        proposal,
+        adminUsdcAccount,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const { withdrawalRecordCount } = await program.account.vault.fetch(vault);
+    const [withdrawalRecord] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("withdrawal_record"),
+        vault.toBuffer(),
+        withdrawalRecordCount.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const executeAccounts = {
+      admin,
+      config,
+      asset,
+      vault,
+      vaultSigner,
+      proposal,
+      proposer: provider.wallet.publicKey,
+      usdcMint,
+      vaultUsdcAccount,
+      destination: adminUsdcAccount,
+      withdrawalRecord,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    try {
+      await program.methods.executeWithdraw("Approved payout").accounts(executeAccounts).rpc();
+      assert.fail("Expected execution to fail below the threshold");
+    } catch (err) {
+      assert.include(err.message, "InsufficientApprovals");
+    }
+
+    await program.methods
+      .approveWithdraw()
+      .accounts({
+        admin,
+        proposal,
+        authority: coAdmin.publicKey,
+      })
+      .signers([coAdmin])
+      .rpc();
+
+    await program.methods.executeWithdraw("Approved payout").accounts(executeAccounts).rpc();
+    assert.isNull(await provider.connection.getAccountInfo(proposal));
+
+    await program.methods.setWithdrawThreshold(1).accounts(updateAdmin).rpc();
+    await program.methods.removeAuthority(coAdmin.publicKey).accounts(updateAdmin).rpc();
+  });
+
+  it("Queues and executes a timelocked deposit limit change", async () => {
+    const { actionNonce } = await program.account.config.fetch(config);
+    const [pendingAction] = await PublicKey.findProgramAddress(
+      [Buffer.from("pending_action"), actionNonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const depositLimit = new anchor.BN(2_000_000_000);
+
+    await program.methods
+      .queueAction(vault, { setDepositLimit: { depositLimit } })
+      .accounts({
+        admin,
+        config,
+        pendingAction,
+        asset,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .executeAction()
+      .accounts({
+        config,
+        pendingAction,
+        proposer: provider.wallet.publicKey,
+        asset,
+        vault,
+        strategy: null,
+      })
+      .rpc();
+
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.depositLimit.toNumber(), depositLimit.toNumber());
+    assert.isNull(await provider.connection.getAccountInfo(pendingAction));
+  });
+
+  it("Restricts price updates to the asset authority", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .updatePrice(new anchor.BN(2_000_000))
+        .accounts({
+          config,
+          asset,
+          vault,
+          authority: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Expected price update from a non-authority to fail");
+    } catch (err) {
+      assert.include(err.message, "Unauthorized");
+    }
+
+    await program.methods
+      .updatePrice(new anchor.BN(1_000_000))
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Updates the deposit limit", async () => {
+    const newLimit = new anchor.BN(2_000_000_000); // 2000 USDC
+
+    await program.methods
+      .updateDepositLimit(newLimit)
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.depositLimit.toNumber(), newLimit.toNumber());
+  });
+
+  it("Enforces the per-user deposit cap", async () => {
+    await program.methods
+      .setPerUserCap(new anchor.BN(1_000_000)) // 1 USDC
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const depositAccounts = {
+      admin,
+      config,
+      asset,
+      vault,
+      vaultSigner,
+      stats,
+      assetMint,
+      usdcMint,
+      userUsdcAccount,
+      vaultUsdcAccount,
+      userAssetAccount,
+      user: provider.wallet.publicKey,
+      referrer: null,
+      referrerUsdcAccount: null,
+      shareClass: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    try {
+      await program.methods
+        .deposit(new anchor.BN(2_000_000), [])
+        .accounts({ ...depositAccounts, position, allowlistEntry: null, blacklistEntry, credential: null })
+        .rpc();
+      assert.fail("Expected deposit above the per-user cap to fail");
+    } catch (err) {
+      assert.include(err.message, "PerUserCapExceeded");
+    }
+
+    await program.methods
+      .setPerUserCap(new anchor.BN(0))
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Restricts deposits to allowlisted wallets", async () => {
+    const [allowlistEntry] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("allowlist"),
+        vault.toBuffer(),
+        provider.wallet.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .setAllowlistEnabled(true)
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .deposit(new anchor.BN(1_000_000), [])
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          stats,
+          assetMint,
+          usdcMint,
+          userUsdcAccount,
+          vaultUsdcAccount,
+          userAssetAccount,
+          user: provider.wallet.publicKey,
+          referrer: null,
+          referrerUsdcAccount: null,
+          position,
+          allowlistEntry: null,
+          blacklistEntry,
+          credential: null,
+          shareClass: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected deposit from a non-allowlisted wallet to fail");
+    } catch (err) {
+      assert.include(err.message, "NotAllowlisted");
+    }
+
+    await program.methods
+      .addToAllowlist(provider.wallet.publicKey)
+      .accounts({
+        asset,
+        vault,
+        allowlistEntry,
+        authority: provider.wallet.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const entry = await program.account.allowlistEntry.fetch(allowlistEntry);
+    assert.equal(entry.wallet.toBase58(), provider.wallet.publicKey.toBase58());
+
+    await program.methods
+      .removeFromAllowlist()
+      .accounts({
+        asset,
+        vault,
+        allowlistEntry,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .setAllowlistEnabled(false)
+      .accounts({
+        config,
+        asset,
+        vault,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Blacklists a wallet and freezes its share account", async () => {
+    const sanctioned = anchor.web3.Keypair.generate();
+    const [blacklist] = await PublicKey.findProgramAddress(
+      [Buffer.from("blacklist"), sanctioned.publicKey.toBuffer()],
+      program.programId
+    );
+    const shareAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      assetMint,
+      sanctioned.publicKey
+    );
+    const freezeAccounts = {
+      admin,
+      asset,
+      vault,
+      vaultSigner,
+      assetMint,
+      shareAccount,
+      blacklistEntry: blacklist,
+      roleGrant: null,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    await program.methods
+      .addToBlacklist(sanctioned.publicKey)
+      .accounts({
+        admin,
+        blacklist,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods.freezeShareAccount().accounts(freezeAccounts).rpc();
+    assert.isTrue((await getAccount(provider.connection, shareAccount)).isFrozen);
+
+    await program.methods
+      .removeFromBlacklist()
+      .accounts({
+        admin,
+        blacklist,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods.thawShareAccount().accounts(freezeAccounts).rpc();
+    assert.isFalse((await getAccount(provider.connection, shareAccount)).isFrozen);
+  });
+
+  it("Places and lifts a legal hold on a holder", async () => {
+    const holder = anchor.web3.Keypair.generate();
+    const shareAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      assetMint,
+      holder.publicKey
+    );
+    const holdAccounts = {
+      admin,
+      asset,
+      vault,
+      vaultSigner,
+      assetMint,
+      shareAccount,
+      roleGrant: null,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // No blacklist entry needed for a legal hold
+    await program.methods.freezeHolder("Court order").accounts(holdAccounts).rpc();
+    assert.isTrue((await getAccount(provider.connection, shareAccount)).isFrozen);
+
+    await program.methods.thawHolder().accounts(holdAccounts).rpc();
+    assert.isFalse((await getAccount(provider.connection, shareAccount)).isFrozen);
+  });
+
+  it("Queues, settles and claims an epoch redemption", async () => {
+    const shares = new anchor.BN(10_000);
+    const vaultAssetAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      assetMint,
+      vaultSigner,
+      anchor.web3.Keypair.generate()
+    );
+    const { redeemRequestNonce, redeemEpoch } = await program.account.vault.fetch(vault);
+    const [request] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("redeem_request"),
+        vault.toBuffer(),
+        redeemRequestNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [epoch] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("redeem_epoch"),
+        vault.toBuffer(),
+        redeemEpoch.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .requestRedeem(shares)
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        request,
+        assetMint,
+        userAssetAccount,
+        vaultAssetAccount,
+        blacklistEntry,
+        user: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    let vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.queuedRedeemShares.toNumber(), shares.toNumber());
+
+    await program.methods
+      .settleRedeemEpoch()
+      .accounts({
+        admin,
+        asset,
+        vault,
+        vaultSigner,
+        assetMint,
+        vaultAssetAccount,
+        usdcMint,
+        vaultUsdcAccount,
+        keeperUsdcAccount: null,
+        epoch,
+        payer: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const { payout } = await program.account.redeemEpoch.fetch(epoch);
+    const balanceBefore = (await getAccount(provider.connection, userUsdcAccount)).amount;
+
+    await program.methods
+      .claimWithdrawal()
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        request,
+        epoch,
+        usdcMint,
+        vaultUsdcAccount,
+        ownerUsdcAccount: userUsdcAccount,
+        owner: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const balanceAfter = (await getAccount(provider.connection, userUsdcAccount)).amount;
+    assert.equal(Number(balanceAfter - balanceBefore), payout.toNumber());
+
+    vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.queuedRedeemShares.toNumber(), 0);
+    assert.equal(vaultAccount.claimableUsdc.toNumber(), 0);
+  });
+
+  it("Ignores direct USDC transfers until the vault is synced", async () => {
+    const donation = 5_000_000; // 5 USDC
+    const before = await program.account.vault.fetch(vault);
+
+    // Send USDC straight to the vault token account, bypassing deposit
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      usdcMint,
+      vaultUsdcAccount,
+      provider.wallet.publicKey,
+      donation
+    );
+
+    let vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.totalUsdc.toNumber(), before.totalUsdc.toNumber());
+
+    await program.methods
+      .sync()
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        usdcMint,
+        vaultUsdcAccount,
+        treasuryUsdcAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(
+      vaultAccount.totalUsdc.toNumber(),
+      before.totalUsdc.toNumber() + donation
+    );
+
+    // Nothing left to reconcile
+    try {
+      await program.methods
+        .sync()
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          usdcMint,
+          vaultUsdcAccount,
+          treasuryUsdcAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Expected sync to fail without a surplus");
+    } catch (err) {
+      assert.include(err.message, "NothingToSync");
+    }
+  });
+
+  it("Accepts deposits in a second stablecoin at its haircut value", async () => {
+    // 9-decimal quote mint taken at a 1% haircut
+    const quoteMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      9
+    );
+    const userQuoteAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      quoteMint,
+      provider.wallet.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      quoteMint,
+      userQuoteAccount,
+      provider.wallet.publicKey,
+      2_000_000_000 // 2 tokens
+    );
+
+    const [quote] = await PublicKey.findProgramAddress(
+      [Buffer.from("quote"), vault.toBuffer(), quoteMint.toBuffer()],
+      program.programId
+    );
+    const [vaultQuoteAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("quote_tokens"), vault.toBuffer(), quoteMint.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .addQuoteMint(100)
+      .accounts({
+        asset,
+        vault,
+        vaultSigner,
+        quote,
+        vaultQuoteAccount,
+        quoteMint,
+        authority: provider.wallet.publicKey,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const before = await program.account.vault.fetch(vault);
+    const statsBefore = await program.account.stats.fetch(stats);
+    const sharesBefore = (await getAccount(provider.connection, userAssetAccount)).amount;
+
+    await program.methods
+      .depositQuote(new anchor.BN(2_000_000_000), [])
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        stats,
+        vaultSigner,
+        assetMint,
+        quote,
+        quoteMint,
+        userQuoteAccount,
+        vaultQuoteAccount,
+        userAssetAccount,
+        user: provider.wallet.publicKey,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 2 tokens less 1% is worth 1.98 USDC; vault USDC is untouched
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(vaultAccount.quoteBacking.toNumber(), 1_980_000);
+    assert.equal(vaultAccount.totalUsdc.toNumber(), before.totalUsdc.toNumber());
+
+    const quoteAccount = await program.account.quoteEntry.fetch(quote);
+    assert.equal(quoteAccount.balance.toNumber(), 2_000_000_000);
+    assert.equal(
+      (await getAccount(provider.connection, vaultQuoteAccount)).amount,
+      BigInt(2_000_000_000)
+    );
+
+    const sharesAfter = (await getAccount(provider.connection, userAssetAccount)).amount;
+    assert.isTrue(sharesAfter > sharesBefore);
+
+    const statsAccount = await program.account.stats.fetch(stats);
+    assert.equal(
+      statsAccount.depositVolume.sub(statsBefore.depositVolume).toNumber(),
+      1_980_000
+    );
+  });
+
+  it("Creates several assets in one transaction", async () => {
+    const { treasury } = await program.account.config.fetch(config);
+    const { signerVersion } = await program.account.admin.fetch(admin);
+    const shareConfig = {
+      decimals: 6,
+      confidential: false,
+      autoApprove: false,
+      auditor: null,
+      transferHook: false,
+      nonTransferable: false,
+    };
+
+    // Both share the first asset's underlying under their own vault ids
+    const vaultIds = [new anchor.BN(1), new anchor.BN(2)];
+    const mints = vaultIds.map((vaultId) => {
+      const [batchAsset] = PublicKey.findProgramAddressSync(
+        [Buffer.from("asset"), usdcMint.toBuffer(), vaultId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("share_mint"), batchAsset.toBuffer()],
+        program.programId
+      )[0];
+    });
+    const remainingAccounts = [];
+    for (const [i, mint] of mints.entries()) {
+      const seeds = [
+        [Buffer.from("asset"), usdcMint.toBuffer(), vaultIds[i].toArrayLike(Buffer, "le", 8)],
+        [Buffer.from("vault"), usdcMint.toBuffer(), vaultIds[i].toArrayLike(Buffer, "le", 8)],
+        [Buffer.from("vault_signer"), mint.toBuffer(), Buffer.from([signerVersion])],
+      ];
+      for (const seed of seeds) {
+        const [pubkey] = PublicKey.findProgramAddressSync(seed, program.programId);
+        remainingAccounts.push({ pubkey, isSigner: false, isWritable: true });
+      }
+      remainingAccounts.push({ pubkey: mint, isSigner: false, isWritable: true });
+      const [tickerClaim] = PublicKey.findProgramAddressSync(
+        [Buffer.from("ticker"), Buffer.from(`BAT${i + 1}`)],
+        program.programId
+      );
+      remainingAccounts.push({ pubkey: tickerClaim, isSigner: false, isWritable: true });
+      const [bond] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bond"), remainingAccounts[i * 7].pubkey.toBuffer()],
+        program.programId
+      );
+      remainingAccounts.push({ pubkey: bond, isSigner: false, isWritable: true });
+      const [assetStats] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stats"), remainingAccounts[i * 7].pubkey.toBuffer()],
+        program.programId
+      );
+      remainingAccounts.push({ pubkey: assetStats, isSigner: false, isWritable: true });
+    }
+
+    await program.methods
+      .batchCreateAssets(
+        ["Batch One", "Batch Two"].map((name, i) => ({
+          vaultId: vaultIds[i],
+          name,
+          ticker: `BAT${i + 1}`,
+          price: new anchor.BN(1_000_000),
+          depositLimit: new anchor.BN(1_000_000_000),
+          shareConfig,
+          category: { stock: {} },
+          tags: 0,
+        }))
+      )
+      .accounts({
+        admin,
+        config,
+        treasury,
+        registry,
+        underlyingMint: usdcMint,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+    for (const [i, mint] of mints.entries()) {
+      const assetAccount = await program.account.asset.fetch(remainingAccounts[i * 7].pubkey);
+      assert.equal(assetAccount.ticker, `BAT${i + 1}`);
+      assert.ok(assetAccount.vaultId.eq(vaultIds[i]));
+      assert.ok(assetAccount.mint.equals(mint));
+      assert.ok(assetAccount.vault.equals(remainingAccounts[i * 7 + 1].pubkey));
+
+      const mintInfo = await getMint(provider.connection, mint);
+      assert.ok(mintInfo.mintAuthority.equals(remainingAccounts[i * 7 + 2].pubkey));
+    }
+
+    const { count } = await program.account.registry.fetch(registry);
+    assert.equal(count.toNumber(), 3);
+  });
+
+  it("Deposits into a share class at the class's own fee", async () => {
+    const [classMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_class_mint"), vault.toBuffer(), Buffer.from([0])],
+      program.programId
+    );
+    const [shareClass] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_class"), vault.toBuffer(), classMint.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .addShareClass(0, 0, 0, new anchor.BN(0))
+      .accounts({
+        asset,
+        vault,
+        vaultSigner,
+        shareClass,
+        classMint,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const userClassAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      classMint,
+      provider.wallet.publicKey
+    );
+    const before = await program.account.vault.fetch(vault);
+
+    await program.methods
+      .deposit(new anchor.BN(1_000_000), [])
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        stats,
+        assetMint: classMint,
+        usdcMint,
+        userUsdcAccount,
+        vaultUsdcAccount,
+        userAssetAccount: userClassAccount,
+        user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // No class fee: the full deposit backs class shares counted in the pool
+    const classShares = (await getAccount(provider.connection, userClassAccount)).amount;
+    const classAccount = await program.account.shareClass.fetch(shareClass);
+    assert.equal(classAccount.supply.toString(), classShares.toString());
+
+    const vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(
+      vaultAccount.totalUsdc.toNumber(),
+      before.totalUsdc.toNumber() + 1_000_000
+    );
+    assert.equal(
+      vaultAccount.totalAssets.sub(before.totalAssets).toString(),
+      classShares.toString()
+    );
+  });
+
+  it("Deposits with a memo and rejects an oversized one", async () => {
+    const before = await program.account.stats.fetch(stats);
+
+    await program.methods
+      .depositWithMemo(new anchor.BN(100_000), [], "desk-7/ref-0042")
+      .accounts({
+        admin,
+        config,
+        asset,
+        vault,
+        vaultSigner,
+        stats,
+        assetMint,
+        usdcMint,
+        userUsdcAccount,
+        vaultUsdcAccount,
+        userAssetAccount,
+        user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const statsAccount = await program.account.stats.fetch(stats);
+    assert.equal(
+      statsAccount.depositCount.toNumber(),
+      before.depositCount.toNumber() + 1
+    );
+
+    try {
+      await program.methods
+        .depositWithMemo(new anchor.BN(100_000), [], "x".repeat(129))
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          stats,
+          assetMint,
+          usdcMint,
+          userUsdcAccount,
+          vaultUsdcAccount,
+          userAssetAccount,
+          user: provider.wallet.publicKey,
+          referrer: null,
+          referrerUsdcAccount: null,
+          position,
+          allowlistEntry: null,
+          blacklistEntry,
+          credential: null,
+          shareClass: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Expected an oversized memo to be rejected");
+    } catch (err) {
+      assert.include(err.message, "MemoTooLong");
+    }
+  });
+
+  it("Restricts strategy harvests and loss reports to their roles", async () => {
+    const adapterProgram = anchor.workspace.LendingAdapter.programId;
+    const [adapterEntry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("adapter"), adapterProgram.toBuffer()],
+      program.programId
+    );
+    const [strategy] = PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy"), vault.toBuffer(), adapterProgram.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .whitelistAdapter()
+      .accounts({
+        admin,
+        adapterEntry,
+        adapterProgram,
+        roleGrant: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .addStrategy()
+      .accounts({
+        asset,
+        vault,
+        vaultSigner,
+        adapterEntry,
+        strategy,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const stranger = anchor.web3.Keypair.generate();
+    const strategyAccounts = (authority: PublicKey) => ({
+      admin,
+      config,
+      asset,
+      vault,
+      vaultSigner,
+      strategy,
+      adapterEntry,
+      adapterProgram,
+      usdcMint,
+      vaultUsdcAccount,
+      keeperUsdcAccount: null,
+      roleGrant: null,
+      authority,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    // Adapters value whatever accounts they're handed, so an open harvest
+    // or loss report could re-mark or write off deployed funds
+    try {
+      await program.methods
+        .harvest()
+        .accounts(strategyAccounts(stranger.publicKey))
+        .signers([stranger])
+        .rpc();
+      assert.fail("Expected a harvest from a non-keeper to fail");
+    } catch (err) {
+      assert.include(err.message, "Unauthorized");
+    }
+    try {
+      await program.methods
+        .reportLoss(new anchor.BN(1))
+        .accounts(strategyAccounts(stranger.publicKey))
+        .signers([stranger])
+        .rpc();
+      assert.fail("Expected a loss report from a non-manager to fail");
+    } catch (err) {
+      assert.include(err.message, "Unauthorized");
+    }
+
+    // Managers can only write off debt the strategy actually carries
+    try {
+      await program.methods
+        .reportLoss(new anchor.BN(1))
+        .accounts(strategyAccounts(provider.wallet.publicKey))
+        .rpc();
+      assert.fail("Expected a loss above the strategy's debt to fail");
+    } catch (err) {
+      assert.include(err.message, "InvalidLoss");
+    }
+
+    const strategyAccount = await program.account.strategy.fetch(strategy);
+    assert.equal(strategyAccount.currentDebt.toNumber(), 0);
+  });
+
+  it("Blocks a redeem in the transaction or slot of a deposit", async () => {
+    const depositAccounts = {
+      admin,
+      config,
+      asset,
+      vault,
+      vaultSigner,
+      stats,
+      assetMint,
+      usdcMint,
+      userUsdcAccount,
+      vaultUsdcAccount,
+      userAssetAccount,
+      user: provider.wallet.publicKey,
+      referrer: null,
+      referrerUsdcAccount: null,
+      position,
+      allowlistEntry: null,
+      blacklistEntry,
+      credential: null,
+      shareClass: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const depositIx = await program.methods
+      .deposit(new anchor.BN(100_000), [])
+      .accounts(depositAccounts)
+      .instruction();
+    try {
+      await program.methods
+        .redeem(new anchor.BN(50_000))
+        .accounts({
+          admin,
+          config,
+          asset,
+          vault,
+          vaultSigner,
+          stats,
+          assetMint,
+          usdcMint,
+          userUsdcAccount,
+          vaultUsdcAccount,
+          userAssetAccount,
+          user: provider.wallet.publicKey,
+          blacklistEntry,
+          position,
+          shareClass: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          usdcTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([depositIx])
+        .rpc();
+      assert.fail("Expected a redeem after a deposit in one transaction to fail");
+    } catch (err) {
+      assert.include(err.message, "SameSlotRedeem");
+    }
+
+    // Every deposit stamps its slot on the position, which redeem checks
+    const signature = await program.methods
+      .deposit(new anchor.BN(100_000), [])
+      .accounts(depositAccounts)
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const positionAccount = await program.account.position.fetch(position);
+    assert.equal(positionAccount.lastDepositSlot.toNumber(), tx.slot);
+  });
+
+  it("Queues a redemption of transfer-hook shares", async () => {
+    const { treasury } = await program.account.config.fetch(config);
+    const { signerVersion } = await program.account.admin.fetch(admin);
+    const vaultId = new anchor.BN(3).toArrayLike(Buffer, "le", 8);
+    const [hookAsset] = PublicKey.findProgramAddressSync(
+      [Buffer.from("asset"), usdcMint.toBuffer(), vaultId],
+      program.programId
+    );
+    const [hookVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), usdcMint.toBuffer(), vaultId],
+      program.programId
+    );
+    const [hookStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), hookAsset.toBuffer()],
+      program.programId
+    );
+    const [hookMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), hookAsset.toBuffer()],
+      program.programId
+    );
+    const [hookSigner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_signer"), hookMint.toBuffer(), Buffer.from([signerVersion])],
+      program.programId
+    );
+    const [hookPosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), hookVault.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createAsset(
+        new anchor.BN(3),
+        "Hook Asset",
+        "HOOK",
+        new anchor.BN(1_000_000),
+        new anchor.BN(1_000_000_000),
+        {
+          decimals: 6,
+          confidential: false,
+          autoApprove: false,
+          auditor: null,
+          transferHook: true,
+          nonTransferable: false,
+        },
+        { stock: {} },
+        0
+      )
+      .accounts({
+        admin,
+        config,
+        treasury,
+        registry,
+        asset: hookAsset,
+        vault: hookVault,
+        tickerClaim: PublicKey.findProgramAddressSync(
+          [Buffer.from("ticker"), Buffer.from("HOOK")],
+          program.programId
+        )[0],
+        bond: PublicKey.findProgramAddressSync(
+          [Buffer.from("bond"), hookAsset.toBuffer()],
+          program.programId
+        )[0],
+        stats: hookStats,
+        vaultSigner: hookSigner,
+        mint: hookMint,
+        underlyingMint: usdcMint,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    await program.methods
+      .initTransferHook()
+      .accounts({
+        asset: hookAsset,
+        extraAccountMetaList: PublicKey.findProgramAddressSync(
+          [Buffer.from("extra-account-metas"), hookMint.toBuffer()],
+          program.programId
+        )[0],
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .openPosition()
+      .accounts({
+        asset: hookAsset,
+        vault: hookVault,
+        position: hookPosition,
+        owner: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const hookUsdcAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      usdcMint,
+      hookSigner,
+      anchor.web3.Keypair.generate()
+    );
+    const userHookAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      hookMint,
+      provider.wallet.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const vaultHookAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      hookMint,
+      hookSigner,
+      anchor.web3.Keypair.generate(),
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .deposit(new anchor.BN(1_000_000), [])
+      .accounts({
+        admin,
+        config,
+        asset: hookAsset,
+        vault: hookVault,
+        vaultSigner: hookSigner,
+        stats: hookStats,
+        assetMint: hookMint,
+        usdcMint,
+        userUsdcAccount,
+        vaultUsdcAccount: hookUsdcAccount,
+        userAssetAccount: userHookAccount,
+        user: provider.wallet.publicKey,
+        referrer: null,
+        referrerUsdcAccount: null,
+        position: hookPosition,
+        allowlistEntry: null,
+        blacklistEntry,
+        credential: null,
+        shareClass: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Token-2022 resolves the hook's extra accounts from what rides along
+    const shares = new anchor.BN(500_000);
+    const hookIx = await createTransferCheckedWithTransferHookInstruction(
+      provider.connection,
+      userHookAccount,
+      hookMint,
+      vaultHookAccount,
+      provider.wallet.publicKey,
+      BigInt(shares.toString()),
+      6,
+      [],
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID
+    );
+    const { redeemRequestNonce } = await program.account.vault.fetch(hookVault);
+    const [request] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("redeem_request"),
+        hookVault.toBuffer(),
+        redeemRequestNonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .requestRedeem(shares)
+      .accounts({
+        admin,
+        config,
+        asset: hookAsset,
+        vault: hookVault,
+        vaultSigner: hookSigner,
+        request,
+        assetMint: hookMint,
+        userAssetAccount: userHookAccount,
+        vaultAssetAccount: vaultHookAccount,
+        blacklistEntry,
+        user: provider.wallet.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(hookIx.keys.slice(4))
+      .rpc();
+
+    const vaultAccount = await program.account.vault.fetch(hookVault);
+    assert.equal(vaultAccount.queuedRedeemShares.toNumber(), shares.toNumber());
+    assert.equal(
+      (await getAccount(provider.connection, vaultHookAccount, undefined, TOKEN_2022_PROGRAM_ID))
+        .amount,
+      BigInt(shares.toString())
+    );
+  });
+
+  it("Swaps shares between vaults and records both legs", async () => {
+    const { signerVersion } = await program.account.admin.fetch(admin);
+    const vaultId = new anchor.BN(1).toArrayLike(Buffer, "le", 8);
+    const [toAsset] = PublicKey.findProgramAddressSync(
+      [Buffer.from("asset"), usdcMint.toBuffer(), vaultId],
+      program.programId
+    );
+    const [toVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), usdcMint.toBuffer(), vaultId],
+      program.programId
+    );
+    const [toMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), toAsset.toBuffer()],
+      program.programId
+    );
+    const [toVaultSigner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_signer"), toMint.toBuffer(), Buffer.from([signerVersion])],
+      program.programId
+    );
+    const [toPosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), toVault.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const [toStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), toAsset.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .openPosition()
+      .accounts({
+        asset: toAsset,
+        vault: toVault,
+        position: toPosition,
+        owner: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const toVaultUsdcAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      usdcMint,
+      toVaultSigner,
+      anchor.web3.Keypair.generate()
+    );
+    const userToAccount = await createAccount(
+      provider.connection,
+      provider.wallet.payer,
+      toMint,
+      provider.wallet.publicKey
+    );
+
+    // The exit is a redemption, so it can't share a slot with a deposit
+    const before = await program.account.position.fetch(position);
+    const fromStatsBefore = await program.account.stats.fetch(stats);
+    while ((await provider.connection.getSlot()) <= before.lastDepositSlot.toNumber()) {}
+
+    await program.methods
+      .swapAssets(new anchor.BN(100_000), [])
+      .accounts({
+        admin,
+        config,
+        fromAsset: asset,
+        fromVault: vault,
+        fromStats: stats,
+        fromVaultSigner: vaultSigner,
+        fromMint: assetMint,
+        fromVaultUsdcAccount: vaultUsdcAccount,
+        userFromAccount: userAssetAccount,
+        fromPosition: position,
+        toAsset,
+        toVault,
+        toStats,
+        toVaultSigner,
+        toMint,
+        toVaultUsdcAccount,
+        userToAccount,
+        toPosition,
+        allowlistEntry: null,
+        usdcMint,
+        user: provider.wallet.publicKey,
+        blacklistEntry,
+        credential: null,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        fromTokenProgram: TOKEN_PROGRAM_ID,
+        toTokenProgram: TOKEN_PROGRAM_ID,
+        usdcTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // What left the first vault is what the second one took in
+    const fromPosition = await program.account.position.fetch(position);
+    const toPositionAccount = await program.account.position.fetch(toPosition);
+    const moved = fromPosition.redeemed.sub(before.redeemed);
+    assert.isTrue(moved.gtn(0));
+    assert.ok(toPositionAccount.deposited.eq(moved));
+    assert.ok(toPositionAccount.lastDepositSlot.gtn(0));
+
+    const toVaultAccount = await program.account.vault.fetch(toVault);
+    assert.ok(toVaultAccount.totalUsdc.eq(moved));
+    assert.equal(
+      (await getAccount(provider.connection, userToAccount)).amount.toString(),
+      toVaultAccount.totalAssets.toString()
+    );
+
+    const fromStats = await program.account.stats.fetch(stats);
+    const toStatsAccount = await program.account.stats.fetch(toStats);
+    assert.equal(
+      fromStats.redeemCount.toNumber(),
+      fromStatsBefore.redeemCount.toNumber() + 1
+    );
+    assert.equal(toStatsAccount.depositCount.toNumber(), 1);
+    assert.ok(toStatsAccount.depositVolume.eq(moved));
   });
 });